@@ -7,3 +7,290 @@ page-id = Page { $num }
 git-description = Git commit {$hash} on {$date}
 books = Books
 expand-description = Expand
+out-of-stock = Out of stock
+unlimited = Unlimited
+sell-copy = Sell one copy
+book-id = ID
+author = Author
+year = Year
+location = Location
+price = Price
+quantity = Quantity
+all = All
+status = Status
+status-history = Status history
+status-available = Available
+status-reserved = Reserved
+status-sold = Sold
+status-withdrawn = Withdrawn
+status-returned = Returned
+make-available = Make available
+reserve = Reserve
+withdraw = Withdraw
+return-copy = Take back returned copy
+returned-on = Returned on {$date}
+sales = Sales
+sale-date = Date
+channel = Channel
+channel-booklooker = Booklooker
+channel-zvab = ZVAB
+channel-shop = Shop
+channel-fair = Book fair
+channel-other = Other
+price-achieved = Price achieved
+shipping = Shipping
+customer = Customer
+optional = Optional
+cancel = Cancel
+record-sale = Record sale
+cannot-sell = { $book } can no longer be sold
+sale-not-recorded = The sale could not be recorded: { $error }
+sale = Sale
+sale-books = Books sold
+add-book = Add book
+customers = Customers
+new-customer = New customer
+edit-customer = Edit customer
+registered-customer = Registered customer
+no-customer = None
+name = Name
+email = Email
+notes = Notes
+address = Address
+addresses = Addresses
+add-address = Add address
+want-list = Want list
+purchase-history = Purchase history
+edit = Edit
+save = Save
+pending-matches = Pending want-list matches
+want-list-match = Matches want-list of { $customer }: { $title }
+show-book = Show book
+dismiss = Dismiss
+want = Wanted
+title = Title
+keywords = Keywords
+comma-separated = Comma separated
+max-price = Maximum price
+remove = Remove
+add-want = Add want
+invoice = Invoice
+delivery-note = Delivery note
+invoice-number = Number
+invoice-date = Date
+delivery-date = Delivery date
+position = Pos.
+description = Description
+amount = Amount
+total = Total
+vat-included = Total includes { $vat } VAT at { $rate } % on a net amount of { $net }.
+margin-scheme-note = Margin scheme – second-hand goods (§ 25a UStG). VAT is not shown separately.
+small-business-note = No VAT is charged under § 19 UStG (small business).
+payment-terms = Payable within { $days } days without deduction.
+tax-number = Tax number:
+shipping-zone = Destination
+zone-domestic = Germany
+zone-eu = EU
+zone-world = World
+postage = Postage
+use = Use
+no-tariff = No tariff for a { $weight } g parcel
+print-label = Print label
+print-labels = Print labels
+scan-mode = Scan mode
+unknown-code = No book with code { $code }
+create-book-with-isbn = Create new book with this ISBN
+new-book = New book
+edit-book = Edit book
+publisher = Publisher
+isbn = ISBN
+weight = Weight (g)
+locations = Locations
+new-location = New location
+unregistered-locations = Locations missing from the register
+unregistered-location = Unregistered: { $location }
+register = Register
+book-count = { $count ->
+    [one] 1 book
+   *[other] { $count } books
+}
+location-kind = Kind
+location-room = Room
+location-shelf = Shelf
+location-box = Box
+part-of = Part of
+no-location = None
+new-name = New name
+rename = Rename
+rename-failed = Couldn't rename: { $error }
+move-selected = Move selected here
+start-stocktaking = Start stocktaking
+open-stocktaking = Open stocktaking of { $location }
+stocktaking-at = Stocktaking: { $location }
+stocktaking-hint = Scan or tick every book you find here, then finish to see what is missing.
+id-or-isbn = ID or ISBN
+add = Add
+expected-books = Expected here
+found-unexpectedly = Found but not expected
+cancel-stocktaking = Cancel stocktaking
+finish-stocktaking = Finish
+continue-stocktaking = Continue checking
+close-stocktaking = Close stocktaking
+missing-books = Missing
+misplaced-books = Recorded elsewhere
+written-off-books = Recorded as sold or withdrawn
+unknown-codes = Unknown codes
+move-here = Move here
+restock = Back into stock
+statistics = Statistics
+stock = Stock
+stock-total = Books in stock
+average-time-in-stock = Average time in stock
+average-age = Average age since publication
+unknown = Unknown
+day-count = { $count ->
+    [one] 1 day
+   *[other] { $count } days
+}
+year-count = { $count ->
+    [one] 1 year
+   *[other] { $count } years
+}
+by-category = By category
+by-condition = By condition
+by-language = By language
+sales-per-month = Sales per month
+slow-movers = Slow movers
+slow-movers-after = Unsold for at least
+purchase-price = Purchase price
+purchase-remainder = Copies costing a cent more
+purchase-date = Purchase date
+differential-taxation = Bought privately (margin scheme, § 25a UStG)
+inventory-valuation = Inventory valuation
+inventory-valuation-at = Inventory valuation at { $date }
+inventory-value = Inventory value
+total-at-cost = Value at cost
+books-without-cost = { $count ->
+    [one] One book has no purchase price and is valued at zero
+   *[other] { $count } books have no purchase price and are valued at zero
+}
+write-down-old-stock = Write down old stock
+export-valuation = Export valuation
+valuation-cost-note = Books on hand valued at purchase cost.
+valuation-write-down-note = Books on hand valued at the lower of purchase cost and market value; old stock is written down by age.
+unit-cost = Unit cost
+unit-market = Unit market
+value = Value
+acquisitions = Acquisitions
+new-lot = New lot
+edit-lot = Edit lot
+lot = Lot
+no-lot = No lot
+lot-source = Bought from
+lot-date = Bought on
+lot-price = Price paid
+profitability = Profitability
+copies-sold = Copies sold
+revenue = Revenue
+cost-of-sold = Cost of copies sold
+margin = Margin on copies sold
+remaining-value = Remaining stock at list price
+remaining-cost = Cost of remaining stock
+lot-profit = Profit on the whole lot
+distribute-cost = Distribute cost
+distribute-cost-hint = Sets the purchase price of each book to its share of the price paid, in proportion to its list price.
+bibliographic-details = Bibliographic details
+binding = Binding
+binding-details = Binding details
+binding-full-leather = Full leather
+binding-half-leather = Half leather
+binding-vellum = Vellum
+binding-cloth = Cloth
+binding-half-cloth = Half cloth
+binding-boards = Boards
+binding-wrappers = Wrappers
+binding-other = Other
+not-specified = Not specified
+illustrations = Illustrations
+collation = Collation
+printing-place = Place of printing
+references = References
+previous-owners = Previous owners
+ex-libris = Ex libris
+inscription = Signature or dedication
+semicolon-separated = Separated by semicolons
+contributors = Contributors
+add-contributor = Add contributor
+surname-first-name = Surname, first name
+role-author = Author
+role-editor = Editor
+role-translator = Translator
+role-illustrator = Illustrator
+authorities = Authorities
+new-authority = New authority
+edit-authority = Edit authority
+authority-kind = Kind
+authority-person = Person
+authority-publisher = Publisher
+aliases = Aliases
+gnd = GND ID
+unregistered-names = Names not in the register
+merge-into = Merge into
+apply-authority = Apply to books
+apply-authority-hint = Replaces every alias in the books with the preferred name.
+delete = Delete
+by-author = By author
+imprint-date-hint = 1923, um 1850, [1923], 1890–1895 or empty if undated
+year-filter-hint = e.g. 1850-1900
+pages = Pages
+page-filter-hint = e.g. 100-300
+sort-by-year = Sort by year
+pagination = Pagination
+format = Format
+format-folio = Folio
+format-quarto = Quarto
+format-octavo = Octavo
+format-duodecimo = Duodecimo
+format-sextodecimo = Sextodecimo
+height-in-cm = Height in cm
+unlisted-format = Format "{ $format }" is not in the list. Pick one to replace it.
+keyword-cloud-hint = Click a keyword to rename it, merge it or list its books.
+similar-keywords = Similar keywords
+merge-here = Merge into this one
+show-books = Show books
+select-all = Select all
+clear-selection = Clear selection
+selected-count = { $count ->
+    [one] 1 book selected
+   *[other] { $count } books selected
+}
+bulk-edit = Edit selected
+sell-selected = Sell selected
+bulk-edit-title = { $count ->
+    [one] Edit 1 book
+   *[other] Edit { $count } books
+}
+unchanged = Unchanged
+category = Category
+price-change = Price change
+set-price = Set to
+change-price-by-percent = Change by percent
+add-keywords = Add keywords
+remove-keywords = Remove keywords
+preview = Preview
+no-changes = No changes
+status-not-allowed = Skipped, the status can't change to the new one
+price-not-applicable = Not applicable, the new price would be too high
+apply = Apply
+undo = Undo
+books-changed = { $count ->
+    [one] Changed 1 book
+   *[other] Changed { $count } books
+}
+redo = Redo
+books-not-saved = { $count ->
+    [one] 1 book could not be saved
+   *[other] { $count } books could not be saved
+}
+book-deleted = Deleted "{ $title }"
+change-history = Changes
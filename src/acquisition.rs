@@ -0,0 +1,212 @@
+//! Lots bought from estates and auctions. Books name the lot they came with in
+//! `BookData::lot`.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::book::{Book, save_book};
+use crate::book_data::format_price;
+use crate::records::{create_record, load_records, write_record};
+use crate::sale::Sale;
+
+/// A lot as stored in the `lots/` register. Amounts are in cents.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lot {
+    /// Taken from the file name, not stored in the file.
+    #[serde(skip)]
+    pub id: u32,
+    /// Estate, auction house or dealer the lot was bought from.
+    pub source: String,
+    pub date: NaiveDate,
+    /// Price paid for the whole lot, including fees.
+    pub price: u32,
+    #[serde(default)]
+    pub notes: String,
+}
+
+pub fn lots_path(data_path: &Path) -> PathBuf {
+    data_path.join("lots")
+}
+
+pub fn load_lots(data_path: &Path) -> Vec<Lot> {
+    load_records(&lots_path(data_path))
+        .into_iter()
+        .map(|(id, lot)| Lot { id, ..lot })
+        .collect()
+}
+
+/// Writes `lot` to the register. New lots (with `id` 0) get the next free
+/// number, which is stored in `lot.id`.
+pub fn save_lot(data_path: &Path, lot: &mut Lot) -> Result<(), Box<dyn std::error::Error>> {
+    if lot.id == 0 {
+        lot.id = create_record(&lots_path(data_path), lot)?;
+        Ok(())
+    } else {
+        write_record(&lots_path(data_path), lot.id, lot)
+    }
+}
+
+/// Indices into `books` of the books that came with the lot `lot_id`.
+pub fn lot_books(books: &[Book], lot_id: u32) -> Vec<usize> {
+    books
+        .iter()
+        .enumerate()
+        .filter(|(_, book)| {
+            book.data
+                .as_ref()
+                .is_some_and(|data| data.lot == Some(lot_id))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Copies of the book `book_id` sold so far and not returned.
+fn copies_sold(sales: &[Sale], book_id: u32) -> u32 {
+    sales
+        .iter()
+        .flat_map(Sale::kept_items)
+        .filter(|item| item.book_id == book_id)
+        .count() as u32
+}
+
+/// Spreads the price of `lot` over its books in proportion to their list price
+/// and the copies that came with the lot, and stores the result as purchase
+/// price and date of each book. The purchase prices of all copies add up to the
+/// lot price. Returns the number of books that could not be saved.
+pub fn distribute_cost(lot: &Lot, books: &mut [Book], sales: &[Sale]) -> usize {
+    let indices = lot_books(books, lot.id);
+    let copies: Vec<u32> = indices
+        .iter()
+        .map(|index| {
+            let book = &books[*index];
+            let quantity = book.data.as_ref().map_or(0, |data| data.quantity);
+            (quantity + copies_sold(sales, book.id)).max(1)
+        })
+        .collect();
+    let weights: Vec<u64> = indices
+        .iter()
+        .zip(&copies)
+        .map(|(index, copies)| {
+            let price = books[*index].data.as_ref().map_or(0, |data| data.price);
+            u64::from(price) * u64::from(*copies)
+        })
+        .collect();
+
+    let mut failed = 0;
+    for ((index, share), copies) in indices.iter().zip(shares(lot.price, &weights)).zip(copies) {
+        let book = &mut books[*index];
+        let Some(data) = book.data.as_mut() else {
+            continue;
+        };
+        // The first copies sold carry the cents that don't divide evenly.
+        data.purchase_price = Some(share / copies);
+        data.purchase_remainder = share % copies;
+        data.purchase_date = Some(lot.date);
+        if let Err(err) = save_book(book) {
+            eprintln!("Failed to save book {:05}: {}", book.id, err);
+            failed += 1;
+        }
+    }
+    failed
+}
+
+/// Splits `total` in proportion to `weights` so that the parts add up to
+/// `total`, handing the cents lost to rounding to the largest remainders. Equal
+/// parts if all weights are zero.
+fn shares(total: u32, weights: &[u64]) -> Vec<u32> {
+    if weights.is_empty() {
+        return vec![];
+    }
+    let weights: Vec<u64> = if weights.iter().all(|weight| *weight == 0) {
+        vec![1; weights.len()]
+    } else {
+        weights.to_vec()
+    };
+    let sum: u64 = weights.iter().sum();
+
+    let mut shares: Vec<u32> = weights
+        .iter()
+        .map(|weight| (u64::from(total) * weight / sum) as u32)
+        .collect();
+    let mut remainders: Vec<(u64, usize)> = weights
+        .iter()
+        .enumerate()
+        .map(|(index, weight)| (u64::from(total) * weight % sum, index))
+        .collect();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let missing = total - shares.iter().sum::<u32>();
+    for (_, index) in remainders.into_iter().take(missing as usize) {
+        shares[index] += 1;
+    }
+    shares
+}
+
+/// How a lot has paid off so far. Amounts are in cents.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LotReport {
+    pub books: u32,
+    pub copies_sold: u32,
+    /// Prices achieved for the copies sold, without shipping.
+    pub revenue: u64,
+    /// Share of the lot price carried by the copies sold.
+    pub cost_of_sold: u64,
+    /// Copies still on hand, at list price.
+    pub remaining_value: u64,
+    /// Share of the lot price carried by the copies still on hand.
+    pub remaining_cost: u64,
+}
+
+impl LotReport {
+    pub fn compute(lot: &Lot, books: &[Book], sales: &[Sale]) -> Self {
+        let mut report = LotReport::default();
+        for index in lot_books(books, lot.id) {
+            let book = &books[index];
+            let data = book.data.as_ref().unwrap();
+            report.books += 1;
+
+            let mut sold = 0;
+            for item in sales
+                .iter()
+                .flat_map(Sale::kept_items)
+                .filter(|item| item.book_id == book.id)
+            {
+                report.copies_sold += 1;
+                report.revenue += u64::from(item.price);
+                if let Some(cost) = data.purchase_price {
+                    report.cost_of_sold +=
+                        u64::from(cost) + u64::from(sold < data.purchase_remainder);
+                }
+                sold += 1;
+            }
+
+            if data.in_stock() && !data.unlimited {
+                let copies = u64::from(data.quantity);
+                report.remaining_value += copies * u64::from(data.price);
+                if let Some(cost) = data.purchase_price {
+                    // The cents over the even share not yet carried by a copy sold.
+                    report.remaining_cost += copies * u64::from(cost)
+                        + u64::from(data.purchase_remainder.saturating_sub(sold));
+                }
+            }
+        }
+        report
+    }
+
+    /// Revenue less the whole lot price; positive once the lot has paid for itself.
+    pub fn profit(&self, lot: &Lot) -> i64 {
+        self.revenue as i64 - i64::from(lot.price)
+    }
+
+    /// Revenue less the cost of the copies sold.
+    pub fn margin(&self) -> i64 {
+        self.revenue as i64 - self.cost_of_sold as i64
+    }
+}
+
+/// Formats a signed amount given in cents, e.g. `-1250` as `-12,50 €`.
+pub fn format_profit(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{sign}{}", format_price(cents.unsigned_abs() as u32))
+}
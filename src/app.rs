@@ -1,26 +1,66 @@
 // SPDX-License-Identifier: MIT
 
-use crate::book::{Book, load_data};
+mod acquisitions;
+mod authorities;
+mod book_form;
+mod bulk_edit;
+mod customers;
+mod keywords;
+mod locations;
+mod statistics;
+mod stocktake;
+
+use crate::acquisition::{Lot, load_lots};
+use crate::audit::{Audit, load_audit};
+use crate::authority::{Authority, AuthorityKind, load_authorities};
+use crate::book::{Book, delete_book, load_data, save_book};
+use crate::book_data::{Binding, BookData, Role, format_price, parse_price};
+use crate::changelog::{LogEntry, appended, load_changes};
 use crate::config::Config;
+use crate::customer::{Customer, WantMatch, load_customers, load_want_matches};
 use crate::fl;
+use crate::history::{BookChange, History};
+use crate::imprint::year_range;
+use crate::invoice::{delivery_note_path, invoice_path, write_invoice};
+use crate::labels::{labels_path, write_labels};
+use crate::location::{Location, LocationKind, load_locations};
+use crate::physical::{BookFormat, page_range};
+use crate::postage::Zone;
+use crate::sale::{Channel, Sale, SaleItem, load_sales, record_return, record_sale};
+use crate::scan::{ScanBuffer, lookup, normalize_isbn};
+use crate::status::Status;
+use acquisitions::{LotForm, LotMessage};
+use authorities::{AuthorityForm, AuthorityMessage, authority_kind_label};
+use book_form::{BookForm, BookFormMessage};
+use bulk_edit::{BulkEditForm, BulkEditMessage};
+use chrono::{Local, NaiveDate};
 use cosmic::app::context_drawer;
 use cosmic::cosmic_config::{self, CosmicConfigEntry};
 use cosmic::iced::alignment::{self, Horizontal, Vertical};
-use cosmic::iced::{Color, Length, Subscription};
+use cosmic::iced::keyboard::{self, Key, key::Named};
+use cosmic::iced::{Color, Event, Length, Subscription, event};
 use cosmic::iced_core::Text;
 use cosmic::iced_wgpu::graphics::text::cosmic_text;
 use cosmic::iced_widget::{Stack, stack};
 use cosmic::widget::button::text;
 use cosmic::widget::icon::Handle;
+use cosmic::widget::menu::key_bind::Modifier;
 use cosmic::widget::{self, about::About, icon, menu, nav_bar};
 use cosmic::widget::{container, scrollable, svg};
 use cosmic::{iced_core, iced_futures, prelude::*};
+use customers::{CustomerForm, CustomerMessage};
 use futures_util::SinkExt;
+use keywords::KeywordMessage;
+use locations::{LocationForm, LocationMessage, location_kind_label};
+use statistics::{SLOW_MOVER_YEARS, StockFigures};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use stocktake::AuditMessage;
 
 const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
 const APP_ICON: &[u8] = include_bytes!("../resources/icons/hicolor/scalable/apps/icon.svg");
+/// Number of the latest changes listed in the book detail.
+const SHOWN_CHANGES: usize = 50;
 
 /// The application model stores app-specific state used to describe its interface and
 /// drive its logic.
@@ -43,6 +83,160 @@ pub struct AppModel {
     watch_is_active: bool,
 
     books: Vec<Book>,
+    /// Index into `books` of the book shown in the detail drawer.
+    selected_book: Option<usize>,
+    /// The latest logged changes of the selected book.
+    book_changes: BookChanges,
+    /// Indices into `books` of the books selected for a bulk edit.
+    book_selection: Vec<usize>,
+    /// The book last clicked, where a shift-click range starts.
+    selection_anchor: Option<usize>,
+    /// Modifier keys held down, which decide what a click on a book does.
+    modifiers: keyboard::Modifiers,
+    /// The bulk edit being entered, if any.
+    bulk_edit_form: Option<BulkEditForm>,
+    /// Changes to books that can be undone and redone.
+    history: History,
+    /// The book being created or edited, if any.
+    book_form: Option<BookForm>,
+    /// Labels for the binding dropdown of the book form, "not specified" first.
+    binding_options: Vec<String>,
+    /// Labels for the format dropdown: "not specified", the foldings in the
+    /// order of `BookFormat::FOLDINGS`, then height in cm.
+    format_options: Vec<String>,
+    /// Labels for the contributor role dropdowns, in the order of `Role::ALL`.
+    role_options: Vec<String>,
+    /// Whether keyboard bursts on the Books page are read as barcode scans.
+    scan_mode: bool,
+    scan_buffer: ScanBuffer,
+    /// The last scanned code that matched no book.
+    unknown_code: Option<String>,
+    /// Only books with this status are listed, all books if `None`.
+    status_filter: Option<Status>,
+    /// Labels for the status filter dropdown, "all" first.
+    status_filter_options: Vec<String>,
+    /// Years the listed books may be from, like "1850-1900". All books if empty.
+    year_filter: String,
+    /// Main page counts the listed books may have, like "100-300". All books
+    /// if empty.
+    page_filter: String,
+    /// Whether books are listed by imprint date instead of by number.
+    sort_by_year: bool,
+    /// Only books with this keyword are listed, all books if `None`.
+    keyword_filter: Option<String>,
+    /// Keyword shown in the keyword drawer.
+    selected_keyword: Option<String>,
+    /// New name entered for the selected keyword.
+    keyword_rename: String,
+    /// Index into `keyword_options` of the keyword to merge the selected one into.
+    keyword_target: Option<usize>,
+    /// All keywords but the selected one.
+    keyword_options: Vec<String>,
+    /// All sales from the ledger, oldest first.
+    sales: Vec<Sale>,
+    /// The sale being entered in the detail drawer, if any.
+    sale_form: Option<SaleForm>,
+    /// Labels for the sales channel dropdown, in the order of `Channel::ALL`.
+    channel_options: Vec<String>,
+    /// Labels for the shipping zone dropdown, in the order of `Zone::ALL`.
+    zone_options: Vec<String>,
+    /// All customers from the register.
+    customers: Vec<Customer>,
+    /// Index into `customers` of the customer shown in the customer drawer.
+    selected_customer: Option<usize>,
+    /// The customer being created or edited, if any.
+    customer_form: Option<CustomerForm>,
+    /// Labels for the customer dropdown in the sale form, "no customer" first.
+    customer_options: Vec<String>,
+    /// All lots from the acquisitions register.
+    lots: Vec<Lot>,
+    /// Index into `lots` of the lot shown in the lot drawer.
+    selected_lot: Option<usize>,
+    /// The lot being created or edited, if any.
+    lot_form: Option<LotForm>,
+    /// Labels for the lot dropdown in the book form, "no lot" first.
+    lot_options: Vec<String>,
+    /// The register of preferred names of persons and publishers.
+    authorities: Vec<Authority>,
+    /// Index into `AuthorityKind::ALL` of the kind shown on the Authorities page.
+    authority_kind: usize,
+    /// Labels for the authority kind dropdowns, in the order of `AuthorityKind::ALL`.
+    authority_kind_options: Vec<String>,
+    /// Index into `authorities` of the entry shown in the authority drawer.
+    selected_authority: Option<usize>,
+    /// The authority being created or edited, if any.
+    authority_form: Option<AuthorityForm>,
+    /// Unregistered names ticked for merging.
+    name_selection: Vec<String>,
+    /// Index into `authority_options` of the entry the ticked names are merged into.
+    authority_target: Option<usize>,
+    /// Names of the registered entries of the shown kind, sorted.
+    authority_options: Vec<String>,
+    /// Books found for customers' want-lists, including those already dismissed.
+    want_matches: Vec<WantMatch>,
+    /// The register of rooms, shelves and boxes.
+    locations: Vec<Location>,
+    /// Index into `locations` of the location shown in the location drawer.
+    selected_location: Option<usize>,
+    /// The location being created, if any.
+    location_form: Option<LocationForm>,
+    /// Indices into `books` of the books ticked in the location drawer.
+    location_selection: Vec<usize>,
+    /// Index into `locations` where the ticked books are moved to.
+    location_target: Option<usize>,
+    /// New name entered for the selected location.
+    location_rename: String,
+    /// Labels for the location dropdowns, "no location" first.
+    location_options: Vec<String>,
+    /// Labels for the location kind dropdown, in the order of `LocationKind::ALL`.
+    location_kind_options: Vec<String>,
+    /// The stocktake in progress, if any.
+    audit: Option<Audit>,
+    /// Whether the audit report is shown instead of the checklist.
+    audit_finished: bool,
+    /// Code typed into the stocktaking drawer.
+    audit_input: String,
+    /// Index into `SLOW_MOVER_YEARS` of the age from which books count as slow movers.
+    slow_mover_years: usize,
+    /// Labels for the slow mover dropdown, in the order of `SLOW_MOVER_YEARS`.
+    slow_mover_options: Vec<String>,
+    /// Whether the inventory valuation writes down old stock.
+    valuation_write_downs: bool,
+    /// Figures of the statistics page, `None` once books, sales or settings
+    /// changed after they were computed.
+    stock_figures: Option<StockFigures>,
+    /// Notifications shown over the page content.
+    toasts: widget::Toasts<Message>,
+}
+
+/// The change log of the selected book, read when the book is selected and
+/// again after a save, rather than on every redraw.
+#[derive(Default)]
+struct BookChanges {
+    /// ID of the book the entries belong to.
+    id: Option<u32>,
+    /// Value of `changelog::appended` when the entries were read.
+    appended: u64,
+    /// The latest changes, newest first, at most `SHOWN_CHANGES`.
+    entries: Vec<LogEntry>,
+}
+
+/// Input state of the form shown when copies are sold. The books sold together
+/// share one invoice and go out in one parcel.
+struct SaleForm {
+    /// Index into `books` of each book sold, one copy each, with the price
+    /// achieved as entered.
+    items: Vec<(usize, String)>,
+    /// ID of a further book to add to the sale, as entered.
+    add_id: String,
+    date: String,
+    channel: usize,
+    shipping: String,
+    /// Index into `Zone::ALL` of the destination, used to suggest postage.
+    zone: usize,
+    customer: String,
+    /// Index into `customers` of the selected registered buyer.
+    customer_id: Option<usize>,
 }
 
 /// Messages emitted by the application and its widgets.
@@ -55,6 +249,59 @@ pub enum Message {
     WatchTick(u32),
     MouseEnterShortDescription(usize),
     MouseExitShortDescription(usize),
+    SelectBook(usize),
+    /// Opens the book, or with Ctrl or Shift held adds it to the selection.
+    ClickBook(usize),
+    ToggleBookSelection(usize, bool),
+    SelectListedBooks,
+    ClearBookSelection,
+    ModifiersChanged(keyboard::Modifiers),
+    BulkEdit(BulkEditMessage),
+    /// A key press no widget handled, checked against the key bindings.
+    Key(keyboard::Modifiers, Key),
+    Undo,
+    Redo,
+    DeleteBook(usize),
+    StartSale(usize),
+    /// Starts a sale of the selected books that can be sold.
+    SellSelection,
+    AddSaleId(String),
+    AddSaleBook,
+    RemoveSaleBook(usize),
+    SaleDate(String),
+    SaleChannel(usize),
+    /// Price achieved for the book in the given line of the sale form.
+    SalePrice(usize, String),
+    SaleShipping(String),
+    SaleZone(usize),
+    UseSuggestedShipping,
+    SaleCustomer(String),
+    SaleRegisteredCustomer(usize),
+    ConfirmSale,
+    CancelSale,
+    ReturnCopy(usize),
+    SetStatus(usize, Status),
+    StatusFilter(usize),
+    YearFilter(String),
+    PageFilter(String),
+    SortByYear(bool),
+    PrintLabel(usize),
+    PrintListedLabels,
+    Customer(CustomerMessage),
+    Lot(LotMessage),
+    Authority(AuthorityMessage),
+    Keyword(KeywordMessage),
+    Location(LocationMessage),
+    Audit(AuditMessage),
+    SlowMoverYears(usize),
+    ValuationWriteDowns(bool),
+    ExportValuation,
+    BookForm(BookFormMessage),
+    ScanMode(bool),
+    ScanKey(char),
+    ScanEnter,
+    DismissUnknownCode,
+    CloseToast(widget::ToastId),
 }
 
 /// Create a COSMIC application from the app model
@@ -97,6 +344,60 @@ impl cosmic::Application for AppModel {
             )
             .activate();
 
+        nav.insert()
+            .text(fl!("locations"))
+            .data::<Page>(Page::Locations)
+            .icon(
+                icon::from_svg_bytes(include_bytes!("../assets/icons/map-pin.svg"))
+                    .symbolic(true)
+                    .icon(),
+            );
+
+        nav.insert()
+            .text(fl!("customers"))
+            .data::<Page>(Page::Customers)
+            .icon(
+                icon::from_svg_bytes(include_bytes!("../assets/icons/users.svg"))
+                    .symbolic(true)
+                    .icon(),
+            );
+
+        nav.insert()
+            .text(fl!("authorities"))
+            .data::<Page>(Page::Authorities)
+            .icon(
+                icon::from_svg_bytes(include_bytes!("../assets/icons/book-user.svg"))
+                    .symbolic(true)
+                    .icon(),
+            );
+
+        nav.insert()
+            .text(fl!("keywords"))
+            .data::<Page>(Page::Keywords)
+            .icon(
+                icon::from_svg_bytes(include_bytes!("../assets/icons/tags.svg"))
+                    .symbolic(true)
+                    .icon(),
+            );
+
+        nav.insert()
+            .text(fl!("acquisitions"))
+            .data::<Page>(Page::Acquisitions)
+            .icon(
+                icon::from_svg_bytes(include_bytes!("../assets/icons/package.svg"))
+                    .symbolic(true)
+                    .icon(),
+            );
+
+        nav.insert()
+            .text(fl!("statistics"))
+            .data::<Page>(Page::Statistics)
+            .icon(
+                icon::from_svg_bytes(include_bytes!("../assets/icons/chart-column.svg"))
+                    .symbolic(true)
+                    .icon(),
+            );
+
         // Create the about widget
         let about = About::default()
             .name(fl!("app-title"))
@@ -121,6 +422,13 @@ impl cosmic::Application for AppModel {
         let books = load_data(std::path::Path::new(&String::from(
             config.clone().data_path,
         )));
+        let sales = load_sales(std::path::Path::new(&config.data_path));
+        let customers = load_customers(std::path::Path::new(&config.data_path));
+        let lots = load_lots(std::path::Path::new(&config.data_path));
+        let authorities = load_authorities(std::path::Path::new(&config.data_path));
+        let want_matches = load_want_matches(std::path::Path::new(&config.data_path));
+        let locations = load_locations(std::path::Path::new(&config.data_path));
+        let audit = load_audit(std::path::Path::new(&config.data_path));
 
         // Construct the app model with the runtime's core.
         let mut app = AppModel {
@@ -128,29 +436,127 @@ impl cosmic::Application for AppModel {
             context_page: ContextPage::default(),
             about,
             nav,
-            key_binds: HashMap::new(),
+            key_binds: key_binds(),
             // Optional configuration file for an application.
             config,
             time: 0,
             watch_is_active: false,
             books,
+            selected_book: None,
+            book_changes: BookChanges::default(),
+            book_selection: Vec::new(),
+            selection_anchor: None,
+            modifiers: keyboard::Modifiers::default(),
+            bulk_edit_form: None,
+            history: History::default(),
+            book_form: None,
+            binding_options: std::iter::once(fl!("not-specified"))
+                .chain(Binding::ALL.into_iter().map(binding_label))
+                .collect(),
+            format_options: std::iter::once(fl!("not-specified"))
+                .chain(BookFormat::FOLDINGS.iter().map(folding_label))
+                .chain(std::iter::once(fl!("height-in-cm")))
+                .collect(),
+            role_options: Role::ALL.into_iter().map(role_label).collect(),
+            scan_mode: false,
+            scan_buffer: ScanBuffer::default(),
+            unknown_code: None,
+            status_filter: None,
+            status_filter_options: std::iter::once(fl!("all"))
+                .chain(Status::ALL.into_iter().map(status_label))
+                .collect(),
+            year_filter: String::new(),
+            page_filter: String::new(),
+            sort_by_year: false,
+            keyword_filter: None,
+            selected_keyword: None,
+            keyword_rename: String::new(),
+            keyword_target: None,
+            keyword_options: Vec::new(),
+            sales,
+            sale_form: None,
+            channel_options: Channel::ALL.into_iter().map(channel_label).collect(),
+            zone_options: Zone::ALL.into_iter().map(zone_label).collect(),
+            customers,
+            selected_customer: None,
+            customer_form: None,
+            customer_options: Vec::new(),
+            lots,
+            selected_lot: None,
+            lot_form: None,
+            lot_options: Vec::new(),
+            authorities,
+            authority_kind: 0,
+            authority_kind_options: AuthorityKind::ALL
+                .into_iter()
+                .map(authority_kind_label)
+                .collect(),
+            selected_authority: None,
+            authority_form: None,
+            name_selection: Vec::new(),
+            authority_target: None,
+            authority_options: Vec::new(),
+            want_matches,
+            locations,
+            selected_location: None,
+            location_form: None,
+            location_selection: Vec::new(),
+            location_target: None,
+            location_rename: String::new(),
+            location_options: Vec::new(),
+            location_kind_options: LocationKind::ALL
+                .into_iter()
+                .map(location_kind_label)
+                .collect(),
+            audit,
+            audit_finished: false,
+            audit_input: String::new(),
+            slow_mover_years: 2,
+            slow_mover_options: SLOW_MOVER_YEARS
+                .into_iter()
+                .map(|years| fl!("year-count", count = years))
+                .collect(),
+            valuation_write_downs: false,
+            stock_figures: None,
+            toasts: widget::Toasts::new(Message::CloseToast),
         };
+        app.refresh_customer_options();
+        app.refresh_lot_options();
+        app.refresh_authority_options();
+        app.refresh_location_options();
 
-        // Create a startup command that sets the window title.
-        let command = app.update_title();
+        // Create a startup command that sets the window title and announces
+        // books that match want-lists since the last run.
+        let command = Task::batch([app.update_title(), app.match_wants()]);
 
         (app, command)
     }
 
     /// Elements to pack at the start of the header bar.
     fn header_start(&self) -> Vec<Element<'_, Self::Message>> {
-        let menu_bar = menu::bar(vec![menu::Tree::with_children(
-            menu::root(fl!("view")).apply(Element::from),
-            menu::items(
-                &self.key_binds,
-                vec![menu::Item::Button(fl!("about"), None, MenuAction::About)],
+        let history_item = |label, enabled, action| match enabled {
+            true => menu::Item::Button(label, None, action),
+            false => menu::Item::ButtonDisabled(label, None, action),
+        };
+        let menu_bar = menu::bar(vec![
+            menu::Tree::with_children(
+                menu::root(fl!("edit")).apply(Element::from),
+                menu::items(
+                    &self.key_binds,
+                    vec![
+                        history_item(fl!("undo"), self.history.can_undo(), MenuAction::Undo),
+                        history_item(fl!("redo"), self.history.can_redo(), MenuAction::Redo),
+                    ],
+                ),
             ),
-        )]);
+            menu::Tree::with_children(
+                menu::root(fl!("view")).apply(Element::from),
+                menu::items(
+                    &self.key_binds,
+                    vec![menu::Item::Button(fl!("about"), None, MenuAction::About)],
+                ),
+            ),
+        ]);
 
         vec![menu_bar.into()]
     }
@@ -172,6 +578,37 @@ impl cosmic::Application for AppModel {
                 |url| Message::LaunchUrl(url.to_string()),
                 Message::ToggleContextPage(ContextPage::About),
             ),
+            ContextPage::BookDetail if self.book_form.is_some() => {
+                self.book_form_drawer(self.book_form.as_ref().unwrap())
+            }
+            ContextPage::BookDetail => {
+                let index = self.selected_book?;
+                let book = self.books.get(index)?;
+                let content = match &self.sale_form {
+                    Some(form) if form.items.iter().any(|(book, _)| *book == index) => {
+                        self.sale_form_view(form)
+                    }
+                    _ => book_detail(
+                        index,
+                        book,
+                        &self.sales,
+                        &self.book_changes.entries,
+                        std::path::Path::new(&self.config.data_path),
+                    ),
+                };
+                context_drawer::context_drawer(
+                    content,
+                    Message::ToggleContextPage(ContextPage::BookDetail),
+                )
+                .title(book.data.clone().unwrap().title)
+            }
+            ContextPage::CustomerDetail => self.customer_drawer()?,
+            ContextPage::LotDetail => self.lot_drawer()?,
+            ContextPage::AuthorityDetail => self.authority_drawer()?,
+            ContextPage::KeywordDetail => self.keyword_drawer()?,
+            ContextPage::LocationDetail => self.location_drawer()?,
+            ContextPage::Audit => self.audit_drawer()?,
+            ContextPage::BulkEdit => self.bulk_edit_drawer()?,
         })
     }
 
@@ -184,11 +621,15 @@ impl cosmic::Application for AppModel {
         let content: Element<_> = match self.nav.active_data::<Page>().unwrap() {
             Page::Books => {
                 let mut books: Vec<_> = self
-                    .books
-                    .iter()
-                    .enumerate()
+                    .listed_books()
+                    .into_iter()
+                    .map(|index| (index, &self.books[index]))
                     .map(|item| {
-                        let title = title(item.1);
+                        let index = item.0;
+                        let selected = widget::checkbox("", self.book_selection.contains(&index))
+                            .on_toggle(move |ticked| Message::ToggleBookSelection(index, ticked));
+                        let title =
+                            widget::mouse_area(title(item.1)).on_press(Message::ClickBook(item.0));
                         let author = author(item.1);
                         let date = date(item.1);
 
@@ -218,20 +659,27 @@ impl cosmic::Application for AppModel {
                         let description = Stack::new().push(description).push_maybe(button);
 
                         container(
-                            widget::column()
-                                .push(title)
+                            widget::row()
+                                .push(selected)
                                 .push(
-                                    widget::row()
-                                        .push(author)
-                                        .push(date)
-                                        .push(widget::horizontal_space())
-                                        .push(widget::Space::with_width(
-                                            Theme::default().cosmic().space_xxs(),
-                                        ))
-                                        .push(description)
-                                        .spacing(Theme::default().cosmic().space_xxs()),
+                                    widget::column()
+                                        .push(title)
+                                        .push(
+                                            widget::row()
+                                                .push(author)
+                                                .push(date)
+                                                .push_maybe(status_badge(item.1))
+                                                .push(widget::horizontal_space())
+                                                .push(widget::Space::with_width(
+                                                    Theme::default().cosmic().space_xxs(),
+                                                ))
+                                                .push(description)
+                                                .spacing(Theme::default().cosmic().space_xxs()),
+                                        )
+                                        .width(Length::Fill),
                                 )
-                                .width(Length::Fill),
+                                .align_y(Vertical::Center)
+                                .spacing(Theme::default().cosmic().space_xxs()),
                         )
                         .width(Length::Fill)
                         .height(Theme::default().cosmic().space_xl())
@@ -246,21 +694,123 @@ impl cosmic::Application for AppModel {
                 let book_list = widget::column().append(&mut books);
                 let table = scrollable(book_list);
 
-                widget::column::with_capacity(1)
+                let filter = widget::row()
+                    .push(widget::text(fl!("status")))
+                    .push(widget::dropdown(
+                        &self.status_filter_options,
+                        Some(self.status_filter.map_or(0, |status| {
+                            Status::ALL.iter().position(|s| *s == status).unwrap() + 1
+                        })),
+                        Message::StatusFilter,
+                    ))
+                    .push(widget::text(fl!("year")))
+                    .push(
+                        widget::text_input(fl!("year-filter-hint"), &self.year_filter)
+                            .on_input(Message::YearFilter)
+                            .width(Length::Fixed(120.0)),
+                    )
+                    .push(widget::text(fl!("pages")))
+                    .push(
+                        widget::text_input(fl!("page-filter-hint"), &self.page_filter)
+                            .on_input(Message::PageFilter)
+                            .width(Length::Fixed(120.0)),
+                    )
+                    .push(
+                        widget::checkbox(fl!("sort-by-year"), self.sort_by_year)
+                            .on_toggle(Message::SortByYear),
+                    )
+                    .push_maybe(self.keyword_filter.as_ref().map(|keyword| {
+                        widget::button::standard(keyword.clone())
+                            .trailing_icon(
+                                icon::from_svg_bytes(include_bytes!("../assets/icons/x.svg"))
+                                    .symbolic(true),
+                            )
+                            .on_press(Message::Keyword(KeywordMessage::ClearFilter))
+                    }))
+                    .push(widget::horizontal_space())
+                    .push(widget::text(fl!("scan-mode")))
+                    .push(widget::toggler(self.scan_mode).on_toggle(Message::ScanMode))
+                    .push(
+                        widget::button::standard(fl!("new-book"))
+                            .on_press(Message::BookForm(BookFormMessage::New(String::new()))),
+                    )
+                    .push(
+                        widget::button::standard(fl!("print-labels"))
+                            .on_press(Message::PrintListedLabels),
+                    )
+                    .push(
+                        widget::button::standard(fl!("select-all"))
+                            .on_press(Message::SelectListedBooks),
+                    )
+                    .align_y(Vertical::Center)
+                    .spacing(space_s);
+
+                let selection = (!self.book_selection.is_empty()).then(|| {
+                    widget::row()
+                        .push(widget::text(fl!(
+                            "selected-count",
+                            count = self.book_selection.len()
+                        )))
+                        .push(
+                            widget::button::suggested(fl!("bulk-edit"))
+                                .on_press(Message::BulkEdit(BulkEditMessage::Open)),
+                        )
+                        .push(
+                            widget::button::standard(fl!("sell-selected"))
+                                .on_press(Message::SellSelection),
+                        )
+                        .push(
+                            widget::button::standard(fl!("clear-selection"))
+                                .on_press(Message::ClearBookSelection),
+                        )
+                        .align_y(Vertical::Center)
+                        .spacing(space_s)
+                });
+
+                let unknown_code = self.unknown_code.as_ref().map(|code| {
+                    let mut row = widget::row()
+                        .push(widget::text(fl!("unknown-code", code = code.clone())))
+                        .push(widget::horizontal_space());
+                    if let Some(isbn) = normalize_isbn(code) {
+                        row = row.push(
+                            widget::button::suggested(fl!("create-book-with-isbn"))
+                                .on_press(Message::BookForm(BookFormMessage::New(isbn))),
+                        );
+                    }
+                    row.push(
+                        widget::button::standard(fl!("dismiss"))
+                            .on_press(Message::DismissUnknownCode),
+                    )
+                    .align_y(Vertical::Center)
+                    .spacing(space_s)
+                });
+
+                widget::column::with_capacity(4)
+                    .push(filter)
+                    .push_maybe(selection)
+                    .push_maybe(unknown_code)
                     .push(table)
+                    .spacing(space_s)
                     .height(Length::Fill)
                     .into()
             }
+            Page::Locations => self.locations_view(),
+            Page::Customers => self.customers_view(),
+            Page::Authorities => self.authorities_view(),
+            Page::Keywords => self.keywords_view(),
+            Page::Acquisitions => self.acquisitions_view(),
+            Page::Statistics => self.statistics_view(),
         };
 
-        widget::container(content)
+        let content = widget::container(content)
             .width(Length::Fill)
             .padding(Theme::default().cosmic().space_m())
             .height(Length::Fill)
             .width(Length::Fill)
             .align_x(Horizontal::Center)
-            .align_y(Vertical::Center)
-            .into()
+            .align_y(Vertical::Center);
+
+        widget::toaster(&self.toasts, content)
     }
 
     /// Register subscriptions for this application.
@@ -282,6 +832,19 @@ impl cosmic::Application for AppModel {
 
                     Message::UpdateConfig(update.config)
                 }),
+            // Tracks Ctrl and Shift for selecting books with the mouse, and
+            // reads the key bindings.
+            event::listen_with(|event, status, _id| match event {
+                Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                    Some(Message::ModifiersChanged(modifiers))
+                }
+                Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. })
+                    if matches!(status, event::Status::Ignored) =>
+                {
+                    Some(Message::Key(modifiers, key))
+                }
+                _ => None,
+            }),
         ];
 
         // Conditionally enables a timer that emits a message every second.
@@ -300,14 +863,43 @@ impl cosmic::Application for AppModel {
             }));
         }
 
+        // Reads barcode scans, which arrive as key presses no widget handled.
+        if self.scan_mode && matches!(self.nav.active_data::<Page>(), Some(Page::Books))
+            || self.is_auditing()
+        {
+            subscriptions.push(keyboard::on_key_press(scan_key));
+        }
+
         Subscription::batch(subscriptions)
     }
 
+    /// Called when a nav item is selected.
+    fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<cosmic::Action<Self::Message>> {
+        // Activate the page in the model.
+        self.nav.activate(id);
+        self.refresh_statistics();
+
+        self.update_title()
+    }
+
     /// Handles messages emitted by the application and its widgets.
     ///
     /// Tasks may be returned for asynchronous execution of code in the background
     /// on the application's async runtime.
     fn update(&mut self, message: Self::Message) -> Task<cosmic::Action<Self::Message>> {
+        let view_only = is_view_only(&message);
+        let task = self.handle_message(message);
+        if !view_only {
+            self.stock_figures = None;
+        }
+        self.refresh_statistics();
+        self.load_book_changes();
+        task
+    }
+}
+
+impl AppModel {
+    fn handle_message(&mut self, message: Message) -> Task<cosmic::Action<Message>> {
         match message {
             Message::WatchTick(time) => {
                 self.time = time;
@@ -346,23 +938,599 @@ impl cosmic::Application for AppModel {
             Message::MouseExitShortDescription(index) => {
                 set_description_hovered(false, &mut self.books[index]);
             }
+
+            Message::SelectBook(index) => {
+                self.selected_book = Some(index);
+                self.sale_form = None;
+                self.book_form = None;
+                self.context_page = ContextPage::BookDetail;
+                self.core.window.show_context = true;
+            }
+
+            Message::ClickBook(index) => {
+                let anchor = self.selection_anchor.filter(|_| self.modifiers.shift());
+                if let Some(anchor) = anchor {
+                    // Selects everything listed between the anchor and the book.
+                    let listed = self.listed_books();
+                    if let (Some(start), Some(end)) = (
+                        listed.iter().position(|listed| *listed == anchor),
+                        listed.iter().position(|listed| *listed == index),
+                    ) {
+                        self.book_selection = listed[start.min(end)..=start.max(end)].to_vec();
+                    }
+                } else if self.modifiers.command() {
+                    let selected = self.book_selection.contains(&index);
+                    self.selection_anchor = Some(index);
+                    return self.update(Message::ToggleBookSelection(index, !selected));
+                } else {
+                    self.selection_anchor = Some(index);
+                    return self.update(Message::SelectBook(index));
+                }
+            }
+
+            Message::ToggleBookSelection(index, ticked) => {
+                self.selection_anchor = Some(index);
+                if ticked {
+                    self.book_selection.push(index);
+                } else {
+                    self.book_selection.retain(|selected| *selected != index);
+                }
+            }
+
+            Message::SelectListedBooks => {
+                self.book_selection = self.listed_books();
+            }
+
+            Message::ClearBookSelection => {
+                self.book_selection.clear();
+            }
+
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+            }
+
+            Message::BulkEdit(message) => return self.update_bulk_edit(message),
+
+            Message::Key(modifiers, key) => {
+                let action = self
+                    .key_binds
+                    .iter()
+                    .find(|(key_bind, _)| key_bind.matches(modifiers, &key))
+                    .map(|(_, action)| *action);
+                if let Some(action) = action {
+                    return self.update(menu::action::MenuAction::message(&action));
+                }
+            }
+
+            Message::Undo => {
+                let count = self.books.len();
+                let data_path = std::path::Path::new(&self.config.data_path);
+                if let Some(failed) = self.history.undo(&mut self.books, data_path) {
+                    if failed > 0 {
+                        eprintln!("Failed to undo changes to {failed} books");
+                    }
+                    return self.after_history_change(count);
+                }
+            }
+
+            Message::Redo => {
+                let count = self.books.len();
+                let data_path = std::path::Path::new(&self.config.data_path);
+                if let Some(failed) = self.history.redo(&mut self.books, data_path) {
+                    if failed > 0 {
+                        eprintln!("Failed to redo changes to {failed} books");
+                    }
+                    return self.after_history_change(count);
+                }
+            }
+
+            Message::DeleteBook(index) => {
+                let book = self.books.remove(index);
+                if let Err(err) = delete_book(std::path::Path::new(&self.config.data_path), &book) {
+                    eprintln!("Failed to delete book {:05}: {}", book.id, err);
+                    self.books.insert(index, book);
+                    return Task::none();
+                }
+                self.history.record(vec![BookChange::deleted(&book)]);
+                self.forget_book_indices();
+
+                let title = book.data.map(|data| data.title).unwrap_or_default();
+                let toast = widget::toaster::Toast::new(fl!("book-deleted", title = title))
+                    .action(fl!("undo"), |_| Message::Undo);
+                return Task::batch([
+                    self.toasts.push(toast).map(cosmic::Action::App),
+                    self.match_wants(),
+                ]);
+            }
+
+            Message::StartSale(index) => {
+                self.sale_form = Some(SaleForm::new(&self.books, &[index]));
+            }
+
+            Message::SellSelection => {
+                let sellable: Vec<usize> = self
+                    .book_selection
+                    .iter()
+                    .copied()
+                    .filter(|index| is_sellable(&self.books[*index]))
+                    .collect();
+                if let Some(first) = sellable.first() {
+                    self.selected_book = Some(*first);
+                    self.book_form = None;
+                    self.sale_form = Some(SaleForm::new(&self.books, &sellable));
+                    self.context_page = ContextPage::BookDetail;
+                    self.core.window.show_context = true;
+                }
+            }
+
+            Message::AddSaleId(id) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    form.add_id = id;
+                }
+            }
+
+            Message::AddSaleBook => {
+                if let Some(index) = self.sale_form.as_ref().and_then(|form| {
+                    form.add_id
+                        .trim()
+                        .parse()
+                        .ok()
+                        .and_then(|id| self.books.iter().position(|book| book.id == id))
+                }) {
+                    self.add_to_sale(index);
+                }
+            }
+
+            Message::RemoveSaleBook(line) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    // The drawer shows the sale only while it has a book.
+                    if form.items.len() > 1 {
+                        form.items.remove(line);
+                    }
+                }
+            }
+
+            Message::SaleDate(date) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    form.date = date;
+                }
+            }
+
+            Message::SaleChannel(channel) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    form.channel = channel;
+                }
+            }
+
+            Message::SalePrice(line, price) => {
+                if let Some((_, form_price)) = self
+                    .sale_form
+                    .as_mut()
+                    .and_then(|form| form.items.get_mut(line))
+                {
+                    *form_price = price;
+                }
+            }
+
+            Message::SaleShipping(shipping) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    form.shipping = shipping;
+                }
+            }
+
+            Message::SaleZone(zone) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    form.zone = zone;
+                }
+            }
+
+            Message::UseSuggestedShipping => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    let books = form
+                        .items
+                        .iter()
+                        .filter_map(|(index, _)| self.books[*index].data.as_ref());
+                    if let Some(tariff) = self.config.postage.cheapest(Zone::ALL[form.zone], books)
+                    {
+                        form.shipping = format_price(tariff.price)
+                            .trim_end_matches(" €")
+                            .to_string();
+                    }
+                }
+            }
+
+            Message::SaleCustomer(customer) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    form.customer = customer;
+                }
+            }
+
+            Message::SaleRegisteredCustomer(selected) => {
+                if let Some(form) = self.sale_form.as_mut() {
+                    form.customer_id = selected.checked_sub(1);
+                    if let Some(customer) = form.customer_id.map(|index| &self.customers[index]) {
+                        form.customer = customer.name.clone();
+                    }
+                }
+            }
+
+            Message::ConfirmSale => {
+                let Some(form) = self.sale_form.as_ref() else {
+                    return Task::none();
+                };
+                let Some(mut sale) = form.to_sale(&self.books, &self.customers) else {
+                    return Task::none();
+                };
+                // Nothing is written unless every copy can still be sold. A
+                // book sold in several copies has a line for each.
+                let mut sold: Vec<(usize, BookData)> = vec![];
+                let mut unsellable = None;
+                for (index, _) in &form.items {
+                    let book = &self.books[*index];
+                    let position = match sold.iter().position(|(sold, _)| sold == index) {
+                        Some(position) => Some(position),
+                        None => book.data.clone().map(|data| {
+                            sold.push((*index, data));
+                            sold.len() - 1
+                        }),
+                    };
+                    if !position.is_some_and(|position| sold[position].1.sell_copy()) {
+                        let title = book.data.as_ref().map_or("", |data| data.title.as_str());
+                        unsellable = Some(format!("{:05} {}", book.id, title));
+                        break;
+                    }
+                }
+                if let Some(book) = unsellable {
+                    return self.toast(fl!("cannot-sell", book = book));
+                }
+
+                // The ledger is written first, so a sale that couldn't be
+                // recorded leaves the books untouched.
+                let data_path = std::path::Path::new(&self.config.data_path);
+                if let Err(err) = record_sale(data_path, &mut sale) {
+                    eprintln!("Failed to record sale: {}", err);
+                    return self.toast(fl!("sale-not-recorded", error = err.to_string()));
+                }
+                self.sale_form = None;
+                for (index, data) in sold {
+                    let book = &mut self.books[index];
+                    book.data = Some(data);
+                    save_book_or_log(book);
+                }
+                if let Err(err) = write_invoice(
+                    data_path,
+                    &self.config.seller,
+                    &sale,
+                    &self.books,
+                    &self.customers,
+                ) {
+                    eprintln!("Failed to write invoice {:05}: {}", sale.id, err);
+                }
+                self.sales.push(sale);
+                return self.match_wants();
+            }
+
+            Message::CancelSale => {
+                self.sale_form = None;
+            }
+
+            Message::Customer(message) => return self.update_customer(message),
+
+            Message::Lot(message) => return self.update_lot(message),
+
+            Message::Authority(message) => return self.update_authority(message),
+
+            Message::Keyword(message) => return self.update_keyword(message),
+
+            Message::Location(message) => return self.update_location(message),
+
+            Message::Audit(message) => return self.update_audit(message),
+
+            Message::SlowMoverYears(selected) => {
+                self.slow_mover_years = selected;
+            }
+
+            Message::ValuationWriteDowns(write_downs) => {
+                self.valuation_write_downs = write_downs;
+            }
+
+            Message::ExportValuation => self.export_valuation(),
+
+            Message::BookForm(message) => {
+                if matches!(message, BookFormMessage::New(_)) {
+                    self.unknown_code = None;
+                }
+                return self.update_book_form(message);
+            }
+
+            Message::ScanMode(scan_mode) => {
+                self.scan_mode = scan_mode;
+                self.scan_buffer = ScanBuffer::default();
+            }
+
+            Message::ScanKey(c) => {
+                self.scan_buffer.push(c, Instant::now());
+            }
+
+            Message::ScanEnter => {
+                if let Some(code) = self.scan_buffer.finish(Instant::now()) {
+                    if self.is_auditing() {
+                        self.record_audit_code(&code);
+                        return Task::none();
+                    }
+                    match lookup(&code, &self.books) {
+                        // Scanning at the till adds to the sale being entered.
+                        Some(index) if self.is_selling() => {
+                            self.unknown_code = None;
+                            self.add_to_sale(index);
+                        }
+                        Some(index) => {
+                            self.unknown_code = None;
+                            return self.update(Message::SelectBook(index));
+                        }
+                        None => self.unknown_code = Some(code),
+                    }
+                }
+            }
+
+            Message::DismissUnknownCode => {
+                self.unknown_code = None;
+            }
+
+            Message::CloseToast(id) => self.toasts.remove(id),
+
+            Message::ReturnCopy(index) => {
+                // Taken off the sale first, so a return that couldn't be
+                // recorded doesn't put the copy back while it still counts as
+                // sold.
+                let data_path = std::path::Path::new(&self.config.data_path);
+                let book = &mut self.books[index];
+                let today = Local::now().date_naive();
+                if let Err(err) = record_return(data_path, &mut self.sales, book.id, today) {
+                    eprintln!("Failed to record return of book {:05}: {}", book.id, err);
+                    return Task::none();
+                }
+                if let Some(data) = book.data.as_mut() {
+                    data.return_copy();
+                    save_book_or_log(book);
+                    return self.match_wants();
+                }
+            }
+
+            Message::SetStatus(index, status) => {
+                let book = &mut self.books[index];
+                let before = book.data.clone();
+                if book
+                    .data
+                    .as_mut()
+                    .is_some_and(|data| data.set_status(status))
+                {
+                    save_book_or_log(book);
+                    if let Some(before) = before {
+                        self.history.record(vec![BookChange::edited(book, before)]);
+                    }
+                    return self.match_wants();
+                }
+            }
+
+            Message::PrintLabel(index) => {
+                self.print_labels(&[&self.books[index]]);
+            }
+
+            Message::PrintListedLabels => {
+                let books: Vec<&Book> = self
+                    .listed_books()
+                    .into_iter()
+                    .map(|index| &self.books[index])
+                    .collect();
+                self.print_labels(&books);
+            }
+
+            Message::StatusFilter(selected) => {
+                self.status_filter = selected
+                    .checked_sub(1)
+                    .map(|position| Status::ALL[position]);
+            }
+
+            Message::YearFilter(years) => {
+                self.year_filter = years;
+            }
+
+            Message::PageFilter(pages) => {
+                self.page_filter = pages;
+            }
+
+            Message::SortByYear(sort) => {
+                self.sort_by_year = sort;
+            }
         }
         Task::none()
     }
+}
 
-    /// Called when a nav item is selected.
-    fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<cosmic::Action<Self::Message>> {
-        // Activate the page in the model.
-        self.nav.activate(id);
+fn key_binds() -> HashMap<menu::KeyBind, MenuAction> {
+    HashMap::from([
+        (
+            menu::KeyBind {
+                modifiers: vec![Modifier::Ctrl],
+                key: Key::Character("z".into()),
+            },
+            MenuAction::Undo,
+        ),
+        (
+            menu::KeyBind {
+                modifiers: vec![Modifier::Ctrl, Modifier::Shift],
+                key: Key::Character("Z".into()),
+            },
+            MenuAction::Redo,
+        ),
+    ])
+}
 
-        self.update_title()
-    }
+/// Whether `message` only changes what is shown or entered, and leaves books,
+/// sales and settings as they are.
+fn is_view_only(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::LaunchUrl(_)
+            | Message::ToggleContextPage(_)
+            | Message::ToggleWatch
+            | Message::WatchTick(_)
+            | Message::MouseEnterShortDescription(_)
+            | Message::MouseExitShortDescription(_)
+            | Message::SelectBook(_)
+            | Message::ClickBook(_)
+            | Message::ToggleBookSelection(..)
+            | Message::SelectListedBooks
+            | Message::ClearBookSelection
+            | Message::ModifiersChanged(_)
+            // Bound keys are handled as the message they are bound to.
+            | Message::Key(..)
+            | Message::StartSale(_)
+            | Message::SellSelection
+            | Message::AddSaleId(_)
+            | Message::AddSaleBook
+            | Message::RemoveSaleBook(_)
+            | Message::SaleDate(_)
+            | Message::SaleChannel(_)
+            | Message::SalePrice(..)
+            | Message::SaleShipping(_)
+            | Message::SaleZone(_)
+            | Message::UseSuggestedShipping
+            | Message::SaleCustomer(_)
+            | Message::SaleRegisteredCustomer(_)
+            | Message::CancelSale
+            | Message::StatusFilter(_)
+            | Message::YearFilter(_)
+            | Message::PageFilter(_)
+            | Message::SortByYear(_)
+            | Message::PrintLabel(_)
+            | Message::PrintListedLabels
+            | Message::ExportValuation
+            | Message::ScanKey(_)
+            | Message::DismissUnknownCode
+            | Message::CloseToast(_)
+    )
 }
 
 fn set_description_hovered(hovered: bool, book: &mut Book) {
     book.description_hovered = hovered;
 }
 
+/// Turns key presses into scanner input: characters and the final Enter.
+fn scan_key(key: Key, _modifiers: keyboard::Modifiers) -> Option<Message> {
+    match key.as_ref() {
+        Key::Character(characters) => characters.chars().next().map(Message::ScanKey),
+        Key::Named(Named::Enter) => Some(Message::ScanEnter),
+        _ => None,
+    }
+}
+
+fn save_book_or_log(book: &Book) {
+    if let Err(err) = save_book(book) {
+        eprintln!("Failed to save book {:05}: {}", book.id, err);
+    }
+}
+
+fn zone_label(zone: Zone) -> String {
+    match zone {
+        Zone::Domestic => fl!("zone-domestic"),
+        Zone::Eu => fl!("zone-eu"),
+        Zone::World => fl!("zone-world"),
+    }
+}
+
+fn channel_label(channel: Channel) -> String {
+    match channel {
+        Channel::Booklooker => fl!("channel-booklooker"),
+        Channel::Zvab => fl!("channel-zvab"),
+        Channel::Shop => fl!("channel-shop"),
+        Channel::Fair => fl!("channel-fair"),
+        Channel::Other => fl!("channel-other"),
+    }
+}
+
+fn status_label(status: Status) -> String {
+    match status {
+        Status::Available => fl!("status-available"),
+        Status::Reserved => fl!("status-reserved"),
+        Status::Sold => fl!("status-sold"),
+        Status::Withdrawn => fl!("status-withdrawn"),
+        Status::Returned => fl!("status-returned"),
+    }
+}
+
+fn role_label(role: Role) -> String {
+    match role {
+        Role::Author => fl!("role-author"),
+        Role::Editor => fl!("role-editor"),
+        Role::Translator => fl!("role-translator"),
+        Role::Illustrator => fl!("role-illustrator"),
+    }
+}
+
+fn binding_label(binding: Binding) -> String {
+    match binding {
+        Binding::FullLeather => fl!("binding-full-leather"),
+        Binding::HalfLeather => fl!("binding-half-leather"),
+        Binding::Vellum => fl!("binding-vellum"),
+        Binding::Cloth => fl!("binding-cloth"),
+        Binding::HalfCloth => fl!("binding-half-cloth"),
+        Binding::Boards => fl!("binding-boards"),
+        Binding::Wrappers => fl!("binding-wrappers"),
+        Binding::Other => fl!("binding-other"),
+    }
+}
+
+/// Label of a field of `BookData` as named in the file, for listing changes.
+fn field_label(field: &str) -> String {
+    match field {
+        "title" => fl!("title"),
+        "author" => fl!("author"),
+        "contributors" => fl!("contributors"),
+        "year" => fl!("year"),
+        "publisher" => fl!("publisher"),
+        "isbn" => fl!("isbn"),
+        "pages" => fl!("pagination"),
+        "format" => fl!("format"),
+        "weight" => fl!("weight"),
+        "location" => fl!("location"),
+        "category" => fl!("category"),
+        "price" => fl!("price"),
+        "purchase_price" => fl!("purchase-price"),
+        "purchase_remainder" => fl!("purchase-remainder"),
+        "purchase_date" => fl!("purchase-date"),
+        "differential_taxation" => fl!("differential-taxation"),
+        "lot" => fl!("lot"),
+        "quantity" => fl!("quantity"),
+        "status" => fl!("status"),
+        "keywords" => fl!("keywords"),
+        "description" => fl!("description"),
+        "binding" => fl!("binding"),
+        "illustrations" => fl!("illustrations"),
+        "collation" => fl!("collation"),
+        "printing_place" => fl!("printing-place"),
+        "references" => fl!("references"),
+        "previous_owners" => fl!("previous-owners"),
+        "ex_libris" => fl!("ex-libris"),
+        "inscription" => fl!("inscription"),
+        field => field.to_string(),
+    }
+}
+
+/// Name of a folding with its sign, like "Octavo (8°)".
+fn folding_label(format: &BookFormat) -> String {
+    let name = match format {
+        BookFormat::Folio => fl!("format-folio"),
+        BookFormat::Quarto => fl!("format-quarto"),
+        BookFormat::Octavo => fl!("format-octavo"),
+        BookFormat::Duodecimo => fl!("format-duodecimo"),
+        BookFormat::Sextodecimo => fl!("format-sextodecimo"),
+        format => return format.to_string(),
+    };
+    format!("{name} ({format})")
+}
+
 fn date(item: &Book) -> cosmic::iced_widget::Row<'_, Message, Theme> {
     widget::row()
         .push(
@@ -400,7 +1568,620 @@ fn title(item: &Book) -> widget::Text<'_, Theme, Renderer> {
     widget::text::heading(item.data.clone().unwrap().title)
 }
 
+fn status_badge(item: &Book) -> Option<widget::Text<'_, Theme, Renderer>> {
+    let data = item.data.as_ref().unwrap();
+    let label = match data.status {
+        Status::Reserved | Status::Sold | Status::Withdrawn => status_label(data.status),
+        Status::Available | Status::Returned if !data.in_stock() => fl!("out-of-stock"),
+        Status::Available | Status::Returned => return None,
+    };
+
+    Some(
+        widget::text::caption_heading(label).class(cosmic::theme::Text::Custom(|theme| {
+            cosmic::iced_widget::text::Style {
+                color: Some(theme.cosmic().destructive_color().into()),
+            }
+        })),
+    )
+}
+
+fn status_action_label(status: Status) -> String {
+    match status {
+        Status::Available => fl!("make-available"),
+        Status::Reserved => fl!("reserve"),
+        Status::Sold => fl!("sell-copy"),
+        Status::Withdrawn => fl!("withdraw"),
+        Status::Returned => fl!("return-copy"),
+    }
+}
+
+fn book_detail<'a>(
+    index: usize,
+    item: &'a Book,
+    sales: &[Sale],
+    changes: &[LogEntry],
+    data_path: &std::path::Path,
+) -> Element<'a, Message> {
+    let data = item.data.clone().unwrap();
+
+    let quantity = if data.unlimited {
+        fl!("unlimited")
+    } else {
+        data.quantity.to_string()
+    };
+
+    let mut sell_button = widget::button::standard(fl!("sell-copy"));
+    if is_sellable(item) {
+        sell_button = sell_button.on_press(Message::StartSale(index));
+    }
+
+    let transitions = data
+        .status
+        .transitions()
+        .iter()
+        .filter(|status| **status != Status::Sold)
+        .fold(widget::row().push(sell_button), |row, status| {
+            let message = match status {
+                Status::Returned => Message::ReturnCopy(index),
+                _ => Message::SetStatus(index, *status),
+            };
+            row.push(widget::button::standard(status_action_label(*status)).on_press(message))
+        })
+        .spacing(Theme::default().cosmic().space_xxs());
+
+    let history = data.status_history.iter().rev().fold(
+        widget::settings::section().title(fl!("status-history")),
+        |section, change| {
+            section.add(widget::settings::item(
+                status_label(change.status),
+                widget::text(change.at.format("%Y-%m-%d %H:%M").to_string()),
+            ))
+        },
+    );
+
+    let changes = changes.iter().fold(
+        widget::settings::section().title(fl!("change-history")),
+        |section, entry| {
+            section.add(widget::settings::item(
+                format!("{} {}", entry.at.format("%Y-%m-%d %H:%M"), entry.user),
+                widget::text(format!(
+                    "{}: {} → {}",
+                    field_label(&entry.field),
+                    entry.old,
+                    entry.new
+                )),
+            ))
+        },
+    );
+
+    let sales = sales
+        .iter()
+        .filter(|sale| sale.contains(item.id))
+        .rev()
+        .fold(
+            widget::settings::section().title(fl!("sales")),
+            |section, sale| {
+                let price = sale
+                    .items
+                    .iter()
+                    .filter(|sale_item| sale_item.book_id == item.id)
+                    .map(|sale_item| sale_item.price)
+                    .sum();
+                let mut summary =
+                    format!("{}, {}", channel_label(sale.channel), format_price(price));
+                if sale.shipping > 0 {
+                    summary.push_str(&format!(
+                        " + {} {}",
+                        format_price(sale.shipping),
+                        fl!("shipping")
+                    ));
+                }
+                if !sale.customer.is_empty() {
+                    summary.push_str(&format!(", {}", sale.customer));
+                }
+                for returned in sale
+                    .items
+                    .iter()
+                    .filter(|sale_item| sale_item.book_id == item.id)
+                    .filter_map(|sale_item| sale_item.returned)
+                {
+                    summary.push_str(&format!(
+                        "\n{}",
+                        fl!("returned-on", date = returned.to_string())
+                    ));
+                }
+
+                let open = |label, path: std::path::PathBuf| {
+                    widget::button::text(label)
+                        .on_press(Message::LaunchUrl(path.to_string_lossy().into_owned()))
+                };
+
+                section
+                    .add(widget::settings::item(
+                        sale.date.to_string(),
+                        widget::text(summary),
+                    ))
+                    .add(
+                        widget::row()
+                            .push(widget::horizontal_space())
+                            .push(open(fl!("invoice"), invoice_path(data_path, sale)))
+                            .push(open(
+                                fl!("delivery-note"),
+                                delivery_note_path(data_path, sale),
+                            )),
+                    )
+            },
+        );
+
+    // Clicking a keyword lists the books that share it.
+    let keywords = widget::flex_row(
+        data.keywords
+            .iter()
+            .map(|keyword| {
+                widget::button::standard(keyword.clone())
+                    .on_press(Message::Keyword(KeywordMessage::Filter(keyword.clone())))
+                    .into()
+            })
+            .collect(),
+    )
+    .row_spacing(Theme::default().cosmic().space_xxs())
+    .column_spacing(Theme::default().cosmic().space_xxs());
+
+    let has_rare_details = data.has_rare_details();
+    let binding = match (data.binding, data.binding_details.as_str()) {
+        (Some(binding), "") => binding_label(binding),
+        (Some(binding), details) => format!("{}, {}", binding_label(binding), details),
+        (None, details) => details.to_string(),
+    };
+    let rare_details = [
+        (fl!("binding"), binding),
+        (fl!("illustrations"), data.illustrations.clone()),
+        (fl!("collation"), data.collation.clone()),
+        (fl!("printing-place"), data.printing_place.clone()),
+        (fl!("references"), data.references.join("; ")),
+        (fl!("previous-owners"), data.previous_owners.join("; ")),
+        (fl!("ex-libris"), data.ex_libris.clone()),
+        (fl!("inscription"), data.inscription.clone()),
+    ]
+    .into_iter()
+    .filter(|(_, value)| !value.is_empty())
+    .fold(
+        widget::settings::section().title(fl!("bibliographic-details")),
+        |section, (label, value)| section.add(widget::settings::item(label, widget::text(value))),
+    );
+
+    // The author line covers the authors; everyone else is listed with their role.
+    let contributors = data
+        .contributors()
+        .into_iter()
+        .filter(|contributor| contributor.role != Role::Author)
+        .fold(
+            widget::settings::section()
+                .add(widget::settings::item(
+                    fl!("book-id"),
+                    widget::text(format!("{:05}", item.id)),
+                ))
+                .add(widget::settings::item(
+                    fl!("author"),
+                    widget::text(data.names(Role::Author)),
+                )),
+            |section, contributor| {
+                section.add(widget::settings::item(
+                    role_label(contributor.role),
+                    widget::text(contributor.display_name()),
+                ))
+            },
+        );
+
+    widget::column()
+        .push(
+            contributors
+                .add(widget::settings::item(
+                    fl!("year"),
+                    widget::text(data.year.to_string()),
+                ))
+                .add(widget::settings::item(
+                    fl!("pagination"),
+                    widget::text(data.pages.to_string()),
+                ))
+                .add(widget::settings::item(
+                    fl!("format"),
+                    widget::text(data.format.to_string()),
+                ))
+                .add(widget::settings::item(
+                    fl!("location"),
+                    widget::text(data.location),
+                ))
+                .add(widget::settings::item(
+                    fl!("price"),
+                    widget::text(format_price(data.price)),
+                ))
+                .add(widget::settings::item(
+                    fl!("quantity"),
+                    widget::text(quantity),
+                ))
+                .add(widget::settings::item(
+                    fl!("status"),
+                    widget::text(status_label(data.status)),
+                )),
+        )
+        .push(keywords)
+        .push_maybe(has_rare_details.then_some(rare_details))
+        .push(transitions)
+        .push(
+            widget::row()
+                .push(
+                    widget::button::standard(fl!("edit"))
+                        .on_press(Message::BookForm(BookFormMessage::Edit(index))),
+                )
+                .push(
+                    widget::button::standard(fl!("print-label"))
+                        .on_press(Message::PrintLabel(index)),
+                )
+                .push(
+                    widget::button::destructive(fl!("delete")).on_press(Message::DeleteBook(index)),
+                )
+                .spacing(Theme::default().cosmic().space_xxs()),
+        )
+        .push(sales)
+        .push(history)
+        .push(changes)
+        .spacing(Theme::default().cosmic().space_s())
+        .into()
+}
+
+impl SaleForm {
+    /// A sale of one copy each of the books at `indices`, at their list prices.
+    fn new(books: &[Book], indices: &[usize]) -> Self {
+        SaleForm {
+            items: indices
+                .iter()
+                .map(|index| {
+                    let price = books[*index].data.as_ref().map_or(0, |data| data.price);
+                    (
+                        *index,
+                        format_price(price).trim_end_matches(" €").to_string(),
+                    )
+                })
+                .collect(),
+            add_id: String::new(),
+            date: Local::now().date_naive().to_string(),
+            channel: Channel::ALL
+                .iter()
+                .position(|channel| *channel == Channel::default())
+                .unwrap(),
+            shipping: String::new(),
+            zone: 0,
+            customer: String::new(),
+            customer_id: None,
+        }
+    }
+
+    /// Builds the sale described by the form, or `None` if an input is invalid.
+    fn to_sale(&self, books: &[Book], customers: &[Customer]) -> Option<Sale> {
+        let shipping = match self.shipping.trim() {
+            "" => 0,
+            shipping => parse_price(shipping)?,
+        };
+        let items = self
+            .items
+            .iter()
+            .map(|(index, price)| {
+                Some(SaleItem {
+                    book_id: books[*index].id,
+                    price: parse_price(price)?,
+                    returned: None,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|items| !items.is_empty())?;
+
+        Some(Sale {
+            id: 0,
+            date: NaiveDate::parse_from_str(self.date.trim(), "%Y-%m-%d").ok()?,
+            channel: Channel::ALL[self.channel],
+            items,
+            shipping,
+            customer: self.customer.trim().to_string(),
+            customer_id: self.customer_id.map(|index| customers[index].id),
+        })
+    }
+}
+
+/// Whether a copy of `book` can be sold.
+fn is_sellable(book: &Book) -> bool {
+    book.data
+        .as_ref()
+        .is_some_and(|data| data.status.is_for_sale() && data.in_stock())
+}
+
 impl AppModel {
+    /// Whether the stocktaking checklist is open, so that scans count as found.
+    fn is_auditing(&self) -> bool {
+        self.audit.is_some()
+            && !self.audit_finished
+            && self.context_page == ContextPage::Audit
+            && self.core.window.show_context
+    }
+
+    /// Whether the sale form is shown, so that scans add to the sale.
+    fn is_selling(&self) -> bool {
+        self.sale_form.is_some()
+            && self.context_page == ContextPage::BookDetail
+            && self.core.window.show_context
+    }
+
+    /// Adds a copy of the book at `index` to the open sale, unless it is in the
+    /// sale already or can't be sold.
+    fn add_to_sale(&mut self, index: usize) {
+        let Some(form) = self.sale_form.as_mut() else {
+            return;
+        };
+        let book = &self.books[index];
+        let Some(data) = book.data.as_ref() else {
+            return;
+        };
+        // Each copy sold has a line of its own.
+        let copies = form.items.iter().filter(|(sold, _)| *sold == index).count();
+        if !is_sellable(book) || (!data.unlimited && copies >= data.quantity as usize) {
+            return;
+        }
+        let price = data.price;
+        form.items.push((
+            index,
+            format_price(price).trim_end_matches(" €").to_string(),
+        ));
+        form.add_id.clear();
+    }
+
+    /// Whether `book` passes the filters of the Books page.
+    fn is_listed(&self, book: &Book) -> bool {
+        let data = book.data.as_ref().unwrap();
+        // An unfinished year or page filter lists everything rather than nothing.
+        self.status_filter
+            .is_none_or(|status| data.status == status)
+            && year_range(&self.year_filter).is_none_or(|(from, to)| data.year.within(from, to))
+            && page_range(&self.page_filter).is_none_or(|(min, max)| {
+                data.pages
+                    .pages
+                    .is_some_and(|pages| (min..=max).contains(&pages))
+            })
+            && self
+                .keyword_filter
+                .as_ref()
+                .is_none_or(|keyword| data.keywords.contains(keyword))
+    }
+
+    /// Refreshes what depends on the books after an undo or redo that started
+    /// with `count` books.
+    fn after_history_change(&mut self, count: usize) -> Task<cosmic::Action<Message>> {
+        if self.books.len() != count {
+            self.forget_book_indices();
+        }
+        self.match_wants()
+    }
+
+    /// Runs `edit`, which changes books in place, and records the books it
+    /// changed as one step that can be undone.
+    fn record_edits<T>(&mut self, edit: impl FnOnce(&mut Self) -> T) -> T {
+        let before: Vec<Option<BookData>> =
+            self.books.iter().map(|book| book.data.clone()).collect();
+        let result = edit(self);
+        let changes = self
+            .books
+            .iter()
+            .zip(before)
+            .filter_map(|(book, before)| {
+                let before = before?;
+                let changed = !book.data.as_ref()?.changes_from(&before).is_empty();
+                changed.then(|| BookChange::edited(book, before))
+            })
+            .collect();
+        self.history.record(changes);
+        result
+    }
+
+    /// Reloads the change log of the selected book when another book was
+    /// selected or a book was saved since it was loaded.
+    fn load_book_changes(&mut self) {
+        let id = self
+            .selected_book
+            .and_then(|index| self.books.get(index))
+            .map(|book| book.id);
+        let appended = appended();
+        if id == self.book_changes.id && appended == self.book_changes.appended {
+            return;
+        }
+        let entries = match id {
+            Some(id) => load_changes(std::path::Path::new(&self.config.data_path), id)
+                .into_iter()
+                .rev()
+                .take(SHOWN_CHANGES)
+                .collect(),
+            None => vec![],
+        };
+        self.book_changes = BookChanges {
+            id,
+            appended,
+            entries,
+        };
+    }
+
+    /// Tells the user in a toast that `failed` books could not be saved, if any.
+    fn report_unsaved(&mut self, failed: usize) -> Task<cosmic::Action<Message>> {
+        if failed == 0 {
+            return Task::none();
+        }
+        self.toast(fl!("books-not-saved", count = failed))
+    }
+
+    /// Shows `text` in a toast.
+    fn toast(&mut self, text: String) -> Task<cosmic::Action<Message>> {
+        self.toasts
+            .push(widget::toaster::Toast::new(text))
+            .map(cosmic::Action::App)
+    }
+
+    /// Drops everything that refers to books by index, for after books were
+    /// deleted or restored and the indices moved.
+    fn forget_book_indices(&mut self) {
+        self.selected_book = None;
+        self.book_selection.clear();
+        self.selection_anchor = None;
+        self.location_selection.clear();
+        self.sale_form = None;
+        self.book_form = None;
+        self.bulk_edit_form = None;
+        if matches!(
+            self.context_page,
+            ContextPage::BookDetail | ContextPage::BulkEdit
+        ) {
+            self.core.window.show_context = false;
+        }
+    }
+
+    /// Indices into `books` of the books on the Books page, in their order.
+    fn listed_books(&self) -> Vec<usize> {
+        let mut listed: Vec<usize> = (0..self.books.len())
+            .filter(|index| self.is_listed(&self.books[*index]))
+            .collect();
+        if self.sort_by_year {
+            listed.sort_by_key(|index| self.books[*index].data.as_ref().unwrap().year);
+        }
+        listed
+    }
+
+    /// Writes shelf labels for `books` and opens them for printing.
+    fn print_labels(&self, books: &[&Book]) {
+        let path = labels_path(std::path::Path::new(&self.config.data_path));
+        if let Err(err) = write_labels(&path, &self.config.label_sheet, books) {
+            eprintln!("Failed to write labels {:#?}: {}", path, err);
+            return;
+        }
+        if let Err(err) = open::that_detached(&path) {
+            eprintln!("failed to open {path:?}: {err}");
+        }
+    }
+
+    fn sale_form_view<'a>(&'a self, form: &'a SaleForm) -> Element<'a, Message> {
+        let postage = &self.config.postage;
+        let weight = postage.parcel_weight(
+            form.items
+                .iter()
+                .filter_map(|(index, _)| self.books[*index].data.as_ref()),
+        );
+        let suggestion: Element<_> = match postage.options(Zone::ALL[form.zone], weight).first() {
+            Some(tariff) => widget::row()
+                .push(widget::text(format!(
+                    "{} {} ({} g): {}",
+                    tariff.carrier,
+                    tariff.name,
+                    weight,
+                    format_price(tariff.price)
+                )))
+                .push(widget::button::text(fl!("use")).on_press(Message::UseSuggestedShipping))
+                .align_y(Vertical::Center)
+                .into(),
+            None => widget::text(fl!("no-tariff", weight = weight.to_string())).into(),
+        };
+
+        let mut confirm = widget::button::suggested(fl!("record-sale"));
+        if form.to_sale(&self.books, &self.customers).is_some() {
+            confirm = confirm.on_press(Message::ConfirmSale);
+        }
+
+        let removable = form.items.len() > 1;
+        let mut items = form.items.iter().enumerate().fold(
+            widget::settings::section().title(fl!("sale-books")),
+            |section, (line, (index, price))| {
+                let book = &self.books[*index];
+                let title = book.data.as_ref().map_or("", |data| data.title.as_str());
+                let mut remove = widget::button::standard(fl!("remove"));
+                if removable {
+                    remove = remove.on_press(Message::RemoveSaleBook(line));
+                }
+                section.add(widget::settings::item(
+                    format!("{:05} {}", book.id, title),
+                    widget::row()
+                        .push(
+                            widget::text_input(fl!("price-achieved"), price)
+                                .on_input(move |price| Message::SalePrice(line, price))
+                                .width(Length::Fixed(100.0)),
+                        )
+                        .push(remove)
+                        .align_y(Vertical::Center)
+                        .spacing(Theme::default().cosmic().space_xxs()),
+                ))
+            },
+        );
+        let mut add = widget::button::standard(fl!("add"));
+        if !form.add_id.trim().is_empty() {
+            add = add.on_press(Message::AddSaleBook);
+        }
+        items = items.add(widget::settings::item(
+            fl!("add-book"),
+            widget::row()
+                .push(
+                    widget::text_input(fl!("book-id"), &form.add_id)
+                        .on_input(Message::AddSaleId)
+                        .on_submit(|_| Message::AddSaleBook)
+                        .width(Length::Fixed(100.0)),
+                )
+                .push(add)
+                .align_y(Vertical::Center)
+                .spacing(Theme::default().cosmic().space_xxs()),
+        ));
+
+        widget::column()
+            .push(items)
+            .push(
+                widget::settings::section()
+                    .title(fl!("sale"))
+                    .add(widget::settings::item(
+                        fl!("sale-date"),
+                        widget::text_input("YYYY-MM-DD", &form.date).on_input(Message::SaleDate),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("channel"),
+                        widget::dropdown(
+                            &self.channel_options,
+                            Some(form.channel),
+                            Message::SaleChannel,
+                        ),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("shipping-zone"),
+                        widget::dropdown(&self.zone_options, Some(form.zone), Message::SaleZone),
+                    ))
+                    .add(widget::settings::item(fl!("postage"), suggestion))
+                    .add(widget::settings::item(
+                        fl!("shipping"),
+                        widget::text_input("0,00", &form.shipping).on_input(Message::SaleShipping),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("registered-customer"),
+                        widget::dropdown(
+                            &self.customer_options,
+                            Some(form.customer_id.map_or(0, |index| index + 1)),
+                            Message::SaleRegisteredCustomer,
+                        ),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("customer"),
+                        widget::text_input(fl!("optional"), &form.customer)
+                            .on_input(Message::SaleCustomer),
+                    )),
+            )
+            .push(
+                widget::row()
+                    .push(widget::button::standard(fl!("cancel")).on_press(Message::CancelSale))
+                    .push(confirm)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
     /// Updates the header and window titles.
     pub fn update_title(&mut self) -> Task<cosmic::Action<Message>> {
         let mut window_title = fl!("app-title");
@@ -421,6 +2202,12 @@ impl AppModel {
 /// The page to display in the application.
 pub enum Page {
     Books,
+    Locations,
+    Customers,
+    Authorities,
+    Keywords,
+    Acquisitions,
+    Statistics,
 }
 
 /// The context page to display in the context drawer.
@@ -428,11 +2215,21 @@ pub enum Page {
 pub enum ContextPage {
     #[default]
     About,
+    BookDetail,
+    CustomerDetail,
+    LotDetail,
+    AuthorityDetail,
+    KeywordDetail,
+    LocationDetail,
+    Audit,
+    BulkEdit,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    Undo,
+    Redo,
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -441,6 +2238,8 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::Undo => Message::Undo,
+            MenuAction::Redo => Message::Redo,
         }
     }
 }
@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: MIT
+
+use super::{AppModel, ContextPage, Message};
+use crate::acquisition::{Lot, LotReport, distribute_cost, format_profit, lot_books, save_lot};
+use crate::book_data::{format_price, parse_price};
+use crate::fl;
+use chrono::{Local, NaiveDate};
+use cosmic::app::context_drawer;
+use cosmic::iced::Length;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget::{self, container, scrollable};
+use std::path::Path;
+
+/// Messages of the Acquisitions page and the lot drawer.
+#[derive(Debug, Clone)]
+pub enum LotMessage {
+    Select(usize),
+    New,
+    Edit,
+    Source(String),
+    Date(String),
+    Price(String),
+    Notes(String),
+    Save,
+    Cancel,
+    /// Spreads the price of the selected lot over its books.
+    DistributeCost,
+}
+
+/// Input state of the lot form.
+pub struct LotForm {
+    lot: Lot,
+    /// `YYYY-MM-DD`.
+    date: String,
+    price: String,
+}
+
+impl LotForm {
+    fn new(lot: Lot) -> Self {
+        LotForm {
+            date: lot.date.to_string(),
+            price: format_price(lot.price).trim_end_matches(" €").to_string(),
+            lot,
+        }
+    }
+
+    /// Builds the lot described by the form, or `None` if an input is invalid.
+    fn to_lot(&self) -> Option<Lot> {
+        let mut lot = self.lot.clone();
+        lot.source = lot.source.trim().to_string();
+        if lot.source.is_empty() {
+            return None;
+        }
+        lot.date = NaiveDate::parse_from_str(self.date.trim(), "%Y-%m-%d").ok()?;
+        lot.price = parse_price(&self.price)?;
+        Some(lot)
+    }
+}
+
+impl AppModel {
+    pub(super) fn acquisitions_view(&self) -> Element<'_, Message> {
+        let space_s = cosmic::theme::spacing().space_s;
+
+        let mut lots: Vec<_> = self
+            .lots
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, lot)| {
+                let report = LotReport::compute(lot, &self.books, &self.sales);
+                container(
+                    widget::mouse_area(
+                        widget::row()
+                            .push(widget::text::heading(format!(
+                                "{} {}",
+                                lot.date, lot.source
+                            )))
+                            .push(widget::horizontal_space())
+                            .push(widget::text(fl!("book-count", count = report.books)))
+                            .push(widget::text(format_price(lot.price)))
+                            .push(widget::text(format_profit(report.profit(lot))))
+                            .align_y(Vertical::Center)
+                            .spacing(space_s)
+                            .width(Length::Fill),
+                    )
+                    .on_press(Message::Lot(LotMessage::Select(index))),
+                )
+                .width(Length::Fill)
+                .height(Theme::default().cosmic().space_xl())
+            })
+            .flat_map(|item| [container(widget::divider::horizontal::default()), item])
+            .collect();
+
+        if !lots.is_empty() {
+            lots.remove(0);
+        }
+
+        widget::column::with_capacity(2)
+            .push(widget::row().push(widget::horizontal_space()).push(
+                widget::button::standard(fl!("new-lot")).on_press(Message::Lot(LotMessage::New)),
+            ))
+            .push(scrollable(widget::column().append(&mut lots)))
+            .spacing(space_s)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub(super) fn lot_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Message>> {
+        let on_close = Message::ToggleContextPage(ContextPage::LotDetail);
+
+        if let Some(form) = &self.lot_form {
+            let title = match form.lot.id {
+                0 => fl!("new-lot"),
+                _ => fl!("edit-lot"),
+            };
+            return Some(
+                context_drawer::context_drawer(self.lot_form_view(form), on_close).title(title),
+            );
+        }
+
+        let lot = self.lots.get(self.selected_lot?)?;
+        Some(
+            context_drawer::context_drawer(self.lot_detail(lot), on_close)
+                .title(format!("{} {}", lot.date, lot.source)),
+        )
+    }
+
+    fn lot_detail<'a>(&'a self, lot: &'a Lot) -> Element<'a, Message> {
+        let report = LotReport::compute(lot, &self.books, &self.sales);
+        let item = |label, value: String| widget::settings::item(label, widget::text(value));
+
+        let details = widget::settings::section()
+            .add(item(fl!("lot-source"), lot.source.clone()))
+            .add(item(
+                fl!("lot-date"),
+                lot.date.format("%d.%m.%Y").to_string(),
+            ))
+            .add(item(fl!("lot-price"), format_price(lot.price)))
+            .add(item(fl!("notes"), lot.notes.clone()));
+
+        let profitability = widget::settings::section()
+            .title(fl!("profitability"))
+            .add(item(fl!("copies-sold"), report.copies_sold.to_string()))
+            .add(item(fl!("revenue"), format_price(report.revenue)))
+            .add(item(fl!("cost-of-sold"), format_price(report.cost_of_sold)))
+            .add(item(fl!("margin"), format_profit(report.margin())))
+            .add(item(
+                fl!("remaining-value"),
+                format_price(report.remaining_value),
+            ))
+            .add(item(
+                fl!("remaining-cost"),
+                format_price(report.remaining_cost),
+            ))
+            .add(item(fl!("lot-profit"), format_profit(report.profit(lot))));
+
+        let books = lot_books(&self.books, lot.id).into_iter().fold(
+            widget::settings::section().title(fl!("books")),
+            |section, index| {
+                let book = &self.books[index];
+                let data = book.data.as_ref().unwrap();
+                section.add(
+                    widget::mouse_area(widget::settings::item(
+                        format!("{:05} {}", book.id, data.title),
+                        widget::text(data.purchase_price.map(format_price).unwrap_or_default()),
+                    ))
+                    .on_press(Message::SelectBook(index)),
+                )
+            },
+        );
+
+        let mut distribute = widget::button::standard(fl!("distribute-cost"));
+        if report.books > 0 {
+            distribute = distribute.on_press(Message::Lot(LotMessage::DistributeCost));
+        }
+
+        widget::column()
+            .push(details)
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("edit"))
+                            .on_press(Message::Lot(LotMessage::Edit)),
+                    )
+                    .push(distribute)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .push(widget::text::caption(fl!("distribute-cost-hint")))
+            .push(profitability)
+            .push(books)
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    fn lot_form_view<'a>(&'a self, form: &'a LotForm) -> Element<'a, Message> {
+        let mut save = widget::button::suggested(fl!("save"));
+        if form.to_lot().is_some() {
+            save = save.on_press(Message::Lot(LotMessage::Save));
+        }
+
+        widget::column()
+            .push(
+                widget::settings::section()
+                    .add(widget::settings::item(
+                        fl!("lot-source"),
+                        widget::text_input("", &form.lot.source)
+                            .on_input(|source| Message::Lot(LotMessage::Source(source))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("lot-date"),
+                        widget::text_input("YYYY-MM-DD", &form.date)
+                            .on_input(|date| Message::Lot(LotMessage::Date(date))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("lot-price"),
+                        widget::text_input("", &form.price)
+                            .on_input(|price| Message::Lot(LotMessage::Price(price))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("notes"),
+                        widget::text_input("", &form.lot.notes)
+                            .on_input(|notes| Message::Lot(LotMessage::Notes(notes))),
+                    )),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::Lot(LotMessage::Cancel)),
+                    )
+                    .push(save)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    pub(super) fn update_lot(&mut self, message: LotMessage) -> Task<cosmic::Action<Message>> {
+        match message {
+            LotMessage::Select(index) => {
+                self.selected_lot = Some(index);
+                self.lot_form = None;
+                self.context_page = ContextPage::LotDetail;
+                self.core.window.show_context = true;
+            }
+
+            LotMessage::New => {
+                self.lot_form = Some(LotForm::new(Lot {
+                    date: Local::now().date_naive(),
+                    ..Lot::default()
+                }));
+                self.context_page = ContextPage::LotDetail;
+                self.core.window.show_context = true;
+            }
+
+            LotMessage::Edit => {
+                if let Some(lot) = self.selected_lot.map(|index| &self.lots[index]) {
+                    self.lot_form = Some(LotForm::new(lot.clone()));
+                }
+            }
+
+            LotMessage::Source(source) => {
+                if let Some(form) = self.lot_form.as_mut() {
+                    form.lot.source = source;
+                }
+            }
+
+            LotMessage::Date(date) => {
+                if let Some(form) = self.lot_form.as_mut() {
+                    form.date = date;
+                }
+            }
+
+            LotMessage::Price(price) => {
+                if let Some(form) = self.lot_form.as_mut() {
+                    form.price = price;
+                }
+            }
+
+            LotMessage::Notes(notes) => {
+                if let Some(form) = self.lot_form.as_mut() {
+                    form.lot.notes = notes;
+                }
+            }
+
+            LotMessage::Save => {
+                let Some(mut lot) = self.lot_form.as_ref().and_then(|form| form.to_lot()) else {
+                    return Task::none();
+                };
+                self.lot_form = None;
+
+                let data_path = Path::new(&self.config.data_path);
+                if let Err(err) = save_lot(data_path, &mut lot) {
+                    eprintln!("Failed to save lot {}: {}", lot.source, err);
+                    return Task::none();
+                }
+
+                match self.lots.iter().position(|l| l.id == lot.id) {
+                    Some(index) => {
+                        self.lots[index] = lot;
+                        self.selected_lot = Some(index);
+                    }
+                    None => {
+                        self.lots.push(lot);
+                        self.selected_lot = Some(self.lots.len() - 1);
+                    }
+                }
+                self.refresh_lot_options();
+            }
+
+            LotMessage::Cancel => {
+                self.lot_form = None;
+            }
+
+            LotMessage::DistributeCost => {
+                if let Some(lot) = self.selected_lot.map(|index| &self.lots[index]) {
+                    let failed = distribute_cost(lot, &mut self.books, &self.sales);
+                    if failed > 0 {
+                        eprintln!("Failed to save the cost of {failed} books");
+                    }
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Rebuilds the labels for the lot dropdown of the book form.
+    pub(super) fn refresh_lot_options(&mut self) {
+        self.lot_options = std::iter::once(fl!("no-lot"))
+            .chain(
+                self.lots
+                    .iter()
+                    .map(|lot| format!("{} {}", lot.date, lot.source)),
+            )
+            .collect();
+    }
+}
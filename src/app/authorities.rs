@@ -0,0 +1,527 @@
+// SPDX-License-Identifier: MIT
+
+use super::book_form::split_list;
+use super::{AppModel, ContextPage, Message};
+use crate::authority::{
+    Authority, AuthorityKind, apply_authority, book_count, find_authority, merge_into,
+    save_authorities, unregistered_names,
+};
+use crate::book_data::sort_name;
+use crate::fl;
+use cosmic::app::context_drawer;
+use cosmic::iced::Length;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget::{self, container, scrollable};
+use std::path::Path;
+
+/// Messages of the Authorities page and the authority drawer.
+#[derive(Debug, Clone)]
+pub enum AuthorityMessage {
+    /// Index into `AuthorityKind::ALL` of the kind shown on the page.
+    Kind(usize),
+    Select(usize),
+    New,
+    /// Adds a name that books use but the register lacks.
+    Register(String),
+    Edit,
+    Name(String),
+    /// Index into `AuthorityKind::ALL`.
+    FormKind(usize),
+    Aliases(String),
+    Gnd(String),
+    Save,
+    Cancel,
+    Delete,
+    /// Rewrites the books to the preferred form of the selected authority.
+    Apply,
+    ToggleName(String, bool),
+    /// Index into the authority options.
+    Target(usize),
+    /// Makes the ticked names aliases of the target and rewrites the books.
+    Merge,
+}
+
+/// Input state of the authority form.
+pub struct AuthorityForm {
+    /// Index into `authorities` of the edited entry, `None` for a new one.
+    authority: Option<usize>,
+    name: String,
+    kind: usize,
+    /// Separated by semicolons, since names may contain commas.
+    aliases: String,
+    gnd: String,
+}
+
+impl AuthorityForm {
+    /// Builds the authority described by the form, or `None` if the name is
+    /// missing or already registered.
+    fn to_authority(&self, authorities: &[Authority]) -> Option<Authority> {
+        let kind = AuthorityKind::ALL[self.kind];
+        let name = match kind {
+            AuthorityKind::Person => sort_name(&self.name),
+            AuthorityKind::Publisher => self.name.trim().to_string(),
+        };
+        if name.is_empty() {
+            return None;
+        }
+        if find_authority(authorities, kind, &name)
+            .is_some_and(|index| Some(index) != self.authority)
+        {
+            return None;
+        }
+
+        Some(Authority {
+            name,
+            kind,
+            aliases: split_list(&self.aliases),
+            gnd: self.gnd.trim().to_string(),
+        })
+    }
+}
+
+impl AppModel {
+    pub(super) fn authorities_view(&self) -> Element<'_, Message> {
+        let space_s = cosmic::theme::spacing().space_s;
+        let space_xxs = Theme::default().cosmic().space_xxs();
+
+        let mut authorities: Vec<_> = self
+            .shown_authorities()
+            .into_iter()
+            .map(|index| {
+                let authority = &self.authorities[index];
+                let count = book_count(&self.books, &self.authorities, index);
+                container(
+                    widget::mouse_area(
+                        widget::row()
+                            .push(widget::text::heading(&authority.name))
+                            .push(widget::text(authority.aliases.join("; ")))
+                            .push(widget::horizontal_space())
+                            .push(widget::text(&authority.gnd))
+                            .push(widget::text(fl!("book-count", count = count)))
+                            .align_y(Vertical::Center)
+                            .spacing(space_s)
+                            .width(Length::Fill),
+                    )
+                    .on_press(Message::Authority(AuthorityMessage::Select(index))),
+                )
+                .width(Length::Fill)
+                .height(Theme::default().cosmic().space_xl())
+            })
+            .flat_map(|item| [container(widget::divider::horizontal::default()), item])
+            .collect();
+
+        if !authorities.is_empty() {
+            authorities.remove(0);
+        }
+
+        let kind = AuthorityKind::ALL[self.authority_kind];
+        let unregistered = unregistered_names(&self.books, &self.authorities, kind);
+        let unregistered = (!unregistered.is_empty()).then(|| {
+            let mut merge = widget::button::standard(fl!("merge-into"));
+            if !self.name_selection.is_empty() && self.authority_target.is_some() {
+                merge = merge.on_press(Message::Authority(AuthorityMessage::Merge));
+            }
+
+            unregistered
+                .into_iter()
+                .fold(
+                    widget::settings::section().title(fl!("unregistered-names")),
+                    |section, (name, count)| {
+                        let ticked = self.name_selection.contains(&name);
+                        section.add(
+                            widget::row()
+                                .push(
+                                    widget::checkbox(format!("{name} ({count})"), ticked)
+                                        .on_toggle({
+                                            let name = name.clone();
+                                            move |ticked| {
+                                                Message::Authority(AuthorityMessage::ToggleName(
+                                                    name.clone(),
+                                                    ticked,
+                                                ))
+                                            }
+                                        }),
+                                )
+                                .push(widget::horizontal_space())
+                                .push(
+                                    widget::button::text(fl!("register")).on_press(
+                                        Message::Authority(AuthorityMessage::Register(name)),
+                                    ),
+                                )
+                                .align_y(Vertical::Center),
+                        )
+                    },
+                )
+                .add(
+                    widget::row()
+                        .push(widget::dropdown(
+                            &self.authority_options,
+                            self.authority_target,
+                            |target| Message::Authority(AuthorityMessage::Target(target)),
+                        ))
+                        .push(merge)
+                        .align_y(Vertical::Center)
+                        .spacing(space_xxs),
+                )
+        });
+
+        widget::column()
+            .push(
+                widget::row()
+                    .push(widget::dropdown(
+                        &self.authority_kind_options,
+                        Some(self.authority_kind),
+                        |kind| Message::Authority(AuthorityMessage::Kind(kind)),
+                    ))
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::standard(fl!("new-authority"))
+                            .on_press(Message::Authority(AuthorityMessage::New)),
+                    )
+                    .align_y(Vertical::Center),
+            )
+            .push(scrollable(
+                widget::column()
+                    .push_maybe(unregistered)
+                    .push(widget::column().append(&mut authorities))
+                    .spacing(space_s),
+            ))
+            .spacing(space_s)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub(super) fn authority_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Message>> {
+        let on_close = Message::ToggleContextPage(ContextPage::AuthorityDetail);
+
+        if let Some(form) = &self.authority_form {
+            let title = match form.authority {
+                None => fl!("new-authority"),
+                Some(_) => fl!("edit-authority"),
+            };
+            return Some(
+                context_drawer::context_drawer(self.authority_form_view(form), on_close)
+                    .title(title),
+            );
+        }
+
+        let index = self.selected_authority?;
+        let authority = self.authorities.get(index)?;
+        Some(
+            context_drawer::context_drawer(self.authority_detail(index), on_close)
+                .title(authority.name.clone()),
+        )
+    }
+
+    fn authority_detail(&self, index: usize) -> Element<'_, Message> {
+        let authority = &self.authorities[index];
+
+        let gnd: Element<_> = match authority.gnd.as_str() {
+            "" => widget::text(fl!("unknown")).into(),
+            gnd => widget::button::link(gnd.to_string())
+                .on_press(Message::LaunchUrl(format!("https://d-nb.info/gnd/{gnd}")))
+                .into(),
+        };
+
+        let books = self
+            .books
+            .iter()
+            .enumerate()
+            .filter(|(_, book)| {
+                book.data
+                    .as_ref()
+                    .is_some_and(|data| authority.used_by(data))
+            })
+            .fold(
+                widget::settings::section().title(fl!("books")),
+                |section, (index, book)| {
+                    let data = book.data.as_ref().unwrap();
+                    section.add(
+                        widget::mouse_area(widget::settings::item(
+                            format!("{:05} {}", book.id, data.title),
+                            widget::text(match authority.kind {
+                                AuthorityKind::Person => data.author.clone(),
+                                AuthorityKind::Publisher => data.publisher.clone(),
+                            }),
+                        ))
+                        .on_press(Message::SelectBook(index)),
+                    )
+                },
+            );
+
+        widget::column()
+            .push(
+                widget::settings::section()
+                    .add(widget::settings::item(
+                        fl!("authority-kind"),
+                        widget::text(authority_kind_label(authority.kind)),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("aliases"),
+                        widget::text(authority.aliases.join("; ")),
+                    ))
+                    .add(widget::settings::item(fl!("gnd"), gnd)),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("edit"))
+                            .on_press(Message::Authority(AuthorityMessage::Edit)),
+                    )
+                    .push(
+                        widget::button::standard(fl!("apply-authority"))
+                            .on_press(Message::Authority(AuthorityMessage::Apply)),
+                    )
+                    .push(widget::horizontal_space())
+                    .push(
+                        widget::button::destructive(fl!("delete"))
+                            .on_press(Message::Authority(AuthorityMessage::Delete)),
+                    )
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .push(widget::text::caption(fl!("apply-authority-hint")))
+            .push(books)
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    fn authority_form_view<'a>(&'a self, form: &'a AuthorityForm) -> Element<'a, Message> {
+        let mut save = widget::button::suggested(fl!("save"));
+        if form.to_authority(&self.authorities).is_some() {
+            save = save.on_press(Message::Authority(AuthorityMessage::Save));
+        }
+
+        widget::column()
+            .push(
+                widget::settings::section()
+                    .add(widget::settings::item(
+                        fl!("name"),
+                        widget::text_input("", &form.name)
+                            .on_input(|name| Message::Authority(AuthorityMessage::Name(name))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("authority-kind"),
+                        widget::dropdown(&self.authority_kind_options, Some(form.kind), |kind| {
+                            Message::Authority(AuthorityMessage::FormKind(kind))
+                        }),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("aliases"),
+                        widget::text_input(fl!("semicolon-separated"), &form.aliases).on_input(
+                            |aliases| Message::Authority(AuthorityMessage::Aliases(aliases)),
+                        ),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("gnd"),
+                        widget::text_input("", &form.gnd)
+                            .on_input(|gnd| Message::Authority(AuthorityMessage::Gnd(gnd))),
+                    )),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::Authority(AuthorityMessage::Cancel)),
+                    )
+                    .push(save)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    pub(super) fn update_authority(
+        &mut self,
+        message: AuthorityMessage,
+    ) -> Task<cosmic::Action<Message>> {
+        match message {
+            AuthorityMessage::Kind(kind) => {
+                self.authority_kind = kind;
+                self.name_selection.clear();
+                self.refresh_authority_options();
+            }
+
+            AuthorityMessage::Select(index) => {
+                self.selected_authority = Some(index);
+                self.authority_form = None;
+                self.context_page = ContextPage::AuthorityDetail;
+                self.core.window.show_context = true;
+            }
+
+            AuthorityMessage::New => {
+                self.authority_form = Some(AuthorityForm {
+                    authority: None,
+                    name: String::new(),
+                    kind: self.authority_kind,
+                    aliases: String::new(),
+                    gnd: String::new(),
+                });
+                self.context_page = ContextPage::AuthorityDetail;
+                self.core.window.show_context = true;
+            }
+
+            AuthorityMessage::Register(name) => {
+                let kind = AuthorityKind::ALL[self.authority_kind];
+                let preferred = match kind {
+                    AuthorityKind::Person => sort_name(&name),
+                    AuthorityKind::Publisher => name.trim().to_string(),
+                };
+                self.name_selection.retain(|selected| *selected != name);
+                self.authorities.push(Authority {
+                    name: preferred,
+                    kind,
+                    ..Authority::default()
+                });
+                self.save_authorities();
+            }
+
+            AuthorityMessage::Edit => {
+                if let Some(index) = self.selected_authority {
+                    let authority = &self.authorities[index];
+                    self.authority_form = Some(AuthorityForm {
+                        authority: Some(index),
+                        name: authority.name.clone(),
+                        kind: AuthorityKind::ALL
+                            .iter()
+                            .position(|kind| *kind == authority.kind)
+                            .unwrap(),
+                        aliases: authority.aliases.join("; "),
+                        gnd: authority.gnd.clone(),
+                    });
+                }
+            }
+
+            AuthorityMessage::Name(name) => {
+                if let Some(form) = self.authority_form.as_mut() {
+                    form.name = name;
+                }
+            }
+
+            AuthorityMessage::FormKind(kind) => {
+                if let Some(form) = self.authority_form.as_mut() {
+                    form.kind = kind;
+                }
+            }
+
+            AuthorityMessage::Aliases(aliases) => {
+                if let Some(form) = self.authority_form.as_mut() {
+                    form.aliases = aliases;
+                }
+            }
+
+            AuthorityMessage::Gnd(gnd) => {
+                if let Some(form) = self.authority_form.as_mut() {
+                    form.gnd = gnd;
+                }
+            }
+
+            AuthorityMessage::Save => {
+                let Some(form) = self.authority_form.take() else {
+                    return Task::none();
+                };
+                let Some(authority) = form.to_authority(&self.authorities) else {
+                    return Task::none();
+                };
+                let index = match form.authority {
+                    Some(index) => {
+                        self.authorities[index] = authority;
+                        index
+                    }
+                    None => {
+                        self.authorities.push(authority);
+                        self.authorities.len() - 1
+                    }
+                };
+                self.save_authorities();
+                return self.update_authority(AuthorityMessage::Select(index));
+            }
+
+            AuthorityMessage::Cancel => {
+                self.authority_form = None;
+            }
+
+            AuthorityMessage::Delete => {
+                if let Some(index) = self.selected_authority.take() {
+                    self.authorities.remove(index);
+                    self.core.window.show_context = false;
+                    self.save_authorities();
+                }
+            }
+
+            AuthorityMessage::Apply => {
+                if let Some(index) = self.selected_authority {
+                    let (_, failed) = self.record_edits(|app| {
+                        apply_authority(&mut app.books, &app.authorities[index])
+                    });
+                    return Task::batch([self.report_unsaved(failed), self.match_wants()]);
+                }
+            }
+
+            AuthorityMessage::ToggleName(name, ticked) => {
+                if ticked {
+                    self.name_selection.push(name);
+                } else {
+                    self.name_selection.retain(|selected| *selected != name);
+                }
+            }
+
+            AuthorityMessage::Target(target) => {
+                self.authority_target = Some(target);
+            }
+
+            AuthorityMessage::Merge => {
+                let Some(index) = self
+                    .authority_target
+                    .and_then(|target| self.shown_authorities().get(target).copied())
+                else {
+                    return Task::none();
+                };
+                let variants = std::mem::take(&mut self.name_selection);
+                let (_, failed) = self.record_edits(|app| {
+                    merge_into(&mut app.authorities, &mut app.books, index, &variants)
+                });
+                self.save_authorities();
+                return Task::batch([self.report_unsaved(failed), self.match_wants()]);
+            }
+        }
+        Task::none()
+    }
+
+    /// Indices into `authorities` of the entries of the kind shown on the page,
+    /// sorted by name.
+    fn shown_authorities(&self) -> Vec<usize> {
+        let kind = AuthorityKind::ALL[self.authority_kind];
+        let mut shown: Vec<usize> = (0..self.authorities.len())
+            .filter(|index| self.authorities[*index].kind == kind)
+            .collect();
+        shown.sort_by_key(|index| self.authorities[*index].name.to_lowercase());
+        shown
+    }
+
+    fn save_authorities(&mut self) {
+        let data_path = Path::new(&self.config.data_path);
+        if let Err(err) = save_authorities(data_path, &self.authorities) {
+            eprintln!("Failed to save authorities: {}", err);
+        }
+        self.refresh_authority_options();
+    }
+
+    /// Rebuilds the labels of the merge target dropdown, in the order of
+    /// `shown_authorities`.
+    pub(super) fn refresh_authority_options(&mut self) {
+        self.authority_target = None;
+        self.authority_options = self
+            .shown_authorities()
+            .into_iter()
+            .map(|index| self.authorities[index].name.clone())
+            .collect();
+    }
+}
+
+pub(super) fn authority_kind_label(kind: AuthorityKind) -> String {
+    match kind {
+        AuthorityKind::Person => fl!("authority-person"),
+        AuthorityKind::Publisher => fl!("authority-publisher"),
+    }
+}
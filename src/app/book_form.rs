@@ -0,0 +1,761 @@
+// SPDX-License-Identifier: MIT
+
+use super::{AppModel, ContextPage, Message, save_book_or_log};
+use crate::authority::{AuthorityKind, suggestions};
+use crate::book::create_book;
+use crate::book_data::{
+    Binding, BookData, Contributor, Role, format_price, parse_price, sort_name,
+};
+use crate::fl;
+use crate::history::BookChange;
+use crate::imprint::ImprintDate;
+use crate::keyword::{complete_keyword, keyword_counts, keyword_suggestions};
+use crate::location::find_location;
+use crate::physical::{BookFormat, Pagination};
+use chrono::NaiveDate;
+use cosmic::app::context_drawer;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget;
+use std::path::Path;
+
+/// Messages of the form for creating and editing books.
+#[derive(Debug, Clone)]
+pub enum BookFormMessage {
+    /// Opens an empty form, prefilled with the given ISBN.
+    New(String),
+    Edit(usize),
+    ContributorName(usize, String),
+    /// Index into `Role::ALL`.
+    ContributorRole(usize, usize),
+    AddContributor,
+    RemoveContributor(usize),
+    Title(String),
+    Year(String),
+    Publisher(String),
+    Isbn(String),
+    Pages(String),
+    /// Index into the format options: none, the foldings, then height.
+    Format(usize),
+    Height(String),
+    /// Index into the location options, 0 for none.
+    Location(usize),
+    Price(String),
+    PurchasePrice(String),
+    PurchaseDate(String),
+    DifferentialTaxation(bool),
+    /// Index into the lot options, 0 for none.
+    Lot(usize),
+    Quantity(String),
+    Weight(String),
+    Keywords(String),
+    Description(String),
+    PreviousOwners(String),
+    ExLibris(String),
+    /// Index into the binding options, 0 for none.
+    Binding(usize),
+    BindingDetails(String),
+    Illustrations(String),
+    Collation(String),
+    PrintingPlace(String),
+    References(String),
+    Inscription(String),
+    Save,
+    Cancel,
+}
+
+/// Input state of the book form.
+pub struct BookForm {
+    /// Index into `books` of the edited book, `None` for a new one.
+    book: Option<usize>,
+    data: BookData,
+    /// Names as entered and indices into `Role::ALL`.
+    contributors: Vec<(String, usize)>,
+    /// Empty if undated.
+    year: String,
+    pages: String,
+    /// Index into the format options, `None` while the book keeps a format
+    /// from before they were a fixed list.
+    format: Option<usize>,
+    /// Height of the spine in cm, used if the format option says so.
+    height: String,
+    price: String,
+    /// Empty if unknown.
+    purchase_price: String,
+    /// `YYYY-MM-DD`, empty if unknown.
+    purchase_date: String,
+    quantity: String,
+    weight: String,
+    /// Comma separated.
+    keywords: String,
+    /// Separated by semicolons, since names may contain commas.
+    previous_owners: String,
+    /// Separated by semicolons.
+    references: String,
+}
+
+impl BookForm {
+    fn new(book: Option<usize>, data: BookData) -> Self {
+        let contributors = data
+            .contributors()
+            .into_iter()
+            .map(|contributor| {
+                let role = Role::ALL.iter().position(|role| *role == contributor.role);
+                (contributor.name, role.unwrap_or(0))
+            })
+            .collect();
+
+        BookForm {
+            book,
+            contributors,
+            year: match data.year {
+                ImprintDate::Unknown => String::new(),
+                year => year.to_string(),
+            },
+            pages: data.pages.to_string(),
+            format: match &data.format {
+                BookFormat::Unknown => Some(0),
+                BookFormat::Height(_) => Some(BookFormat::FOLDINGS.len() + 1),
+                BookFormat::Other(_) => None,
+                folding => BookFormat::FOLDINGS
+                    .iter()
+                    .position(|known| known == folding)
+                    .map(|index| index + 1),
+            },
+            height: data
+                .format
+                .height()
+                .map(|cm| cm.to_string())
+                .unwrap_or_default(),
+            price: format_price(data.price).trim_end_matches(" €").to_string(),
+            purchase_price: data
+                .purchase_price
+                .map(|cost| format_price(cost).trim_end_matches(" €").to_string())
+                .unwrap_or_default(),
+            purchase_date: data
+                .purchase_date
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            quantity: data.quantity.to_string(),
+            weight: data.weight.to_string(),
+            keywords: data.keywords.join(", "),
+            previous_owners: data.previous_owners.join("; "),
+            references: data.references.join("; "),
+            data,
+        }
+    }
+
+    /// Builds the book data described by the form, or `None` if an input is invalid.
+    fn to_book_data(&self) -> Option<BookData> {
+        let mut data = self.data.clone();
+        data.set_contributors(
+            self.contributors
+                .iter()
+                .filter(|(name, _)| !name.trim().is_empty())
+                .map(|(name, role)| Contributor {
+                    name: sort_name(name),
+                    role: Role::ALL[*role],
+                })
+                .collect(),
+        );
+        data.title = data.title.trim().to_string();
+        if data.title.is_empty() {
+            return None;
+        }
+        data.isbn = data.isbn.trim().to_string();
+        data.pages = Pagination::parse(&self.pages);
+        data.format = match self.format {
+            None => data.format,
+            Some(0) => BookFormat::Unknown,
+            Some(index) if index <= BookFormat::FOLDINGS.len() => {
+                BookFormat::FOLDINGS[index - 1].clone()
+            }
+            Some(_) => BookFormat::Height(self.height.trim().parse().ok().filter(|cm| *cm > 0)?),
+        };
+        data.year = ImprintDate::parse(&self.year)?;
+        data.price = parse_price(&self.price)?;
+        let purchase_price = match self.purchase_price.trim() {
+            "" => None,
+            cost => Some(parse_price(cost)?),
+        };
+        // A price entered by hand no longer comes from spreading a lot price.
+        if purchase_price != data.purchase_price {
+            data.purchase_remainder = 0;
+        }
+        data.purchase_price = purchase_price;
+        data.purchase_date = match self.purchase_date.trim() {
+            "" => None,
+            date => Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?),
+        };
+        data.quantity = self.quantity.trim().parse().ok()?;
+        data.weight = self.weight.trim().parse().ok()?;
+        data.keywords = self
+            .keywords
+            .split(',')
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+        data.previous_owners = split_list(&self.previous_owners);
+        data.references = split_list(&self.references);
+        for field in [
+            &mut data.ex_libris,
+            &mut data.binding_details,
+            &mut data.illustrations,
+            &mut data.collation,
+            &mut data.printing_place,
+            &mut data.inscription,
+        ] {
+            *field = field.trim().to_string();
+        }
+
+        Some(data)
+    }
+}
+
+/// Splits a list separated by semicolons, dropping empty entries.
+pub(super) fn split_list(list: &str) -> Vec<String> {
+    list.split(';')
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+impl AppModel {
+    pub(super) fn book_form_drawer<'a>(
+        &'a self,
+        form: &'a BookForm,
+    ) -> context_drawer::ContextDrawer<'a, Message> {
+        let title = match form.book {
+            Some(_) => fl!("edit-book"),
+            None => fl!("new-book"),
+        };
+        context_drawer::context_drawer(
+            self.book_form_view(form),
+            Message::ToggleContextPage(ContextPage::BookDetail),
+        )
+        .title(title)
+    }
+
+    fn book_form_view<'a>(&'a self, form: &'a BookForm) -> Element<'a, Message> {
+        let data = &form.data;
+        let input = |label: String, value: &'a str, message: fn(String) -> BookFormMessage| {
+            widget::settings::item(
+                label,
+                widget::text_input("", value)
+                    .on_input(move |text| Message::BookForm(message(text))),
+            )
+        };
+
+        // Books still carry free text locations from before the register existed,
+        // which the picker can't show.
+        let selected = find_location(&self.locations, &data.location).map(|index| index + 1);
+        let location = widget::column()
+            .push(widget::dropdown(
+                &self.location_options,
+                selected.or(data.location.trim().is_empty().then_some(0)),
+                |location| Message::BookForm(BookFormMessage::Location(location)),
+            ))
+            .push_maybe(
+                (selected.is_none() && !data.location.trim().is_empty()).then(|| {
+                    widget::text::caption(fl!(
+                        "unregistered-location",
+                        location = data.location.clone()
+                    ))
+                }),
+            );
+
+        // Formats from before the fixed list stay until another one is picked.
+        let measured = form.format == Some(BookFormat::FOLDINGS.len() + 1);
+        let format = widget::column()
+            .push(widget::dropdown(
+                &self.format_options,
+                form.format,
+                |format| Message::BookForm(BookFormMessage::Format(format)),
+            ))
+            .push_maybe(measured.then(|| {
+                widget::text_input(fl!("height-in-cm"), &form.height)
+                    .on_input(|height| Message::BookForm(BookFormMessage::Height(height)))
+            }))
+            .push_maybe(form.format.is_none().then(|| {
+                widget::text::caption(fl!("unlisted-format", format = data.format.to_string()))
+            }));
+
+        let contributors = form.contributors.iter().enumerate().fold(
+            widget::settings::section().title(fl!("contributors")),
+            |section, (index, (name, role))| {
+                let row = widget::row()
+                    .push(
+                        widget::text_input(fl!("surname-first-name"), name).on_input(move |name| {
+                            Message::BookForm(BookFormMessage::ContributorName(index, name))
+                        }),
+                    )
+                    .push(widget::dropdown(
+                        &self.role_options,
+                        Some(*role),
+                        move |role| {
+                            Message::BookForm(BookFormMessage::ContributorRole(index, role))
+                        },
+                    ))
+                    .push(
+                        widget::button::text(fl!("remove"))
+                            .on_press(Message::BookForm(BookFormMessage::RemoveContributor(index))),
+                    )
+                    .align_y(Vertical::Center)
+                    .spacing(Theme::default().cosmic().space_xxs());
+                section.add(widget::column().push(row).push(self.name_suggestions(
+                    AuthorityKind::Person,
+                    name,
+                    move |name| BookFormMessage::ContributorName(index, name),
+                )))
+            },
+        );
+
+        let mut save = widget::button::suggested(fl!("save"));
+        if form.to_book_data().is_some() {
+            save = save.on_press(Message::BookForm(BookFormMessage::Save));
+        }
+
+        widget::column()
+            .push(contributors)
+            .push(
+                widget::button::standard(fl!("add-contributor"))
+                    .on_press(Message::BookForm(BookFormMessage::AddContributor)),
+            )
+            .push(
+                widget::settings::section()
+                    .add(input(fl!("title"), &data.title, BookFormMessage::Title))
+                    .add(widget::settings::item(
+                        fl!("year"),
+                        widget::text_input(fl!("imprint-date-hint"), &form.year)
+                            .on_input(|year| Message::BookForm(BookFormMessage::Year(year))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("publisher"),
+                        widget::column()
+                            .push(
+                                widget::text_input("", &data.publisher).on_input(|publisher| {
+                                    Message::BookForm(BookFormMessage::Publisher(publisher))
+                                }),
+                            )
+                            .push(self.name_suggestions(
+                                AuthorityKind::Publisher,
+                                &data.publisher,
+                                BookFormMessage::Publisher,
+                            )),
+                    ))
+                    .add(input(fl!("isbn"), &data.isbn, BookFormMessage::Isbn))
+                    .add(widget::settings::item(
+                        fl!("pagination"),
+                        widget::text_input("XII, 345 S., 12 Taf.", &form.pages)
+                            .on_input(|pages| Message::BookForm(BookFormMessage::Pages(pages))),
+                    ))
+                    .add(widget::settings::item(fl!("format"), format))
+                    .add(widget::settings::item(fl!("location"), location))
+                    .add(input(fl!("price"), &form.price, BookFormMessage::Price))
+                    .add(input(
+                        fl!("purchase-price"),
+                        &form.purchase_price,
+                        BookFormMessage::PurchasePrice,
+                    ))
+                    .add(widget::settings::item(
+                        fl!("lot"),
+                        widget::dropdown(
+                            &self.lot_options,
+                            Some(
+                                data.lot
+                                    .and_then(|id| self.lots.iter().position(|lot| lot.id == id))
+                                    .map_or(0, |index| index + 1),
+                            ),
+                            |lot| Message::BookForm(BookFormMessage::Lot(lot)),
+                        ),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("purchase-date"),
+                        widget::text_input("YYYY-MM-DD", &form.purchase_date).on_input(|date| {
+                            Message::BookForm(BookFormMessage::PurchaseDate(date))
+                        }),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("differential-taxation"),
+                        widget::checkbox("", data.differential_taxation).on_toggle(|ticked| {
+                            Message::BookForm(BookFormMessage::DifferentialTaxation(ticked))
+                        }),
+                    ))
+                    .add(input(
+                        fl!("quantity"),
+                        &form.quantity,
+                        BookFormMessage::Quantity,
+                    ))
+                    .add(input(fl!("weight"), &form.weight, BookFormMessage::Weight))
+                    .add(widget::settings::item(
+                        fl!("keywords"),
+                        widget::column()
+                            .push(
+                                widget::text_input(fl!("comma-separated"), &form.keywords)
+                                    .on_input(|keywords| {
+                                        Message::BookForm(BookFormMessage::Keywords(keywords))
+                                    }),
+                            )
+                            .push(self.keyword_completions(&form.keywords)),
+                    ))
+                    .add(input(
+                        fl!("description"),
+                        &data.description,
+                        BookFormMessage::Description,
+                    )),
+            )
+            .push(
+                widget::settings::section()
+                    .title(fl!("bibliographic-details"))
+                    .add(widget::settings::item(
+                        fl!("binding"),
+                        widget::dropdown(
+                            &self.binding_options,
+                            Some(data.binding.map_or(0, |binding| {
+                                Binding::ALL.iter().position(|b| *b == binding).unwrap() + 1
+                            })),
+                            |binding| Message::BookForm(BookFormMessage::Binding(binding)),
+                        ),
+                    ))
+                    .add(input(
+                        fl!("binding-details"),
+                        &data.binding_details,
+                        BookFormMessage::BindingDetails,
+                    ))
+                    .add(input(
+                        fl!("illustrations"),
+                        &data.illustrations,
+                        BookFormMessage::Illustrations,
+                    ))
+                    .add(input(
+                        fl!("collation"),
+                        &data.collation,
+                        BookFormMessage::Collation,
+                    ))
+                    .add(input(
+                        fl!("printing-place"),
+                        &data.printing_place,
+                        BookFormMessage::PrintingPlace,
+                    ))
+                    .add(widget::settings::item(
+                        fl!("references"),
+                        widget::text_input(fl!("semicolon-separated"), &form.references).on_input(
+                            |references| Message::BookForm(BookFormMessage::References(references)),
+                        ),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("previous-owners"),
+                        widget::text_input(fl!("semicolon-separated"), &form.previous_owners)
+                            .on_input(|owners| {
+                                Message::BookForm(BookFormMessage::PreviousOwners(owners))
+                            }),
+                    ))
+                    .add(input(
+                        fl!("ex-libris"),
+                        &data.ex_libris,
+                        BookFormMessage::ExLibris,
+                    ))
+                    .add(input(
+                        fl!("inscription"),
+                        &data.inscription,
+                        BookFormMessage::Inscription,
+                    )),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::BookForm(BookFormMessage::Cancel)),
+                    )
+                    .push(save)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    /// Registered names of `kind` that complete `input`, as buttons that take
+    /// over the preferred form.
+    fn name_suggestions<'a>(
+        &'a self,
+        kind: AuthorityKind,
+        input: &str,
+        message: impl Fn(String) -> BookFormMessage + 'a,
+    ) -> Element<'a, Message> {
+        suggestions(&self.authorities, kind, input, 5)
+            .into_iter()
+            .fold(widget::row(), |row, name| {
+                row.push(
+                    widget::button::text(name)
+                        .on_press(Message::BookForm(message(name.to_string()))),
+                )
+            })
+            .spacing(Theme::default().cosmic().space_xxs())
+            .into()
+    }
+
+    /// Keywords in use that complete the one being typed, as buttons that
+    /// take it over.
+    fn keyword_completions(&self, input: &str) -> Element<'_, Message> {
+        let counts = keyword_counts(&self.books);
+        keyword_suggestions(&counts, input, 5)
+            .into_iter()
+            .fold(widget::row(), |row, keyword| {
+                row.push(
+                    widget::button::text(keyword.to_string()).on_press(Message::BookForm(
+                        BookFormMessage::Keywords(complete_keyword(input, keyword)),
+                    )),
+                )
+            })
+            .spacing(Theme::default().cosmic().space_xxs())
+            .into()
+    }
+
+    pub(super) fn update_book_form(
+        &mut self,
+        message: BookFormMessage,
+    ) -> Task<cosmic::Action<Message>> {
+        match message {
+            BookFormMessage::New(isbn) => {
+                let data = BookData {
+                    isbn,
+                    quantity: 1,
+                    ..BookData::default()
+                };
+                self.book_form = Some(BookForm::new(None, data));
+                self.sale_form = None;
+                self.context_page = ContextPage::BookDetail;
+                self.core.window.show_context = true;
+            }
+
+            BookFormMessage::Edit(index) => {
+                if let Some(data) = &self.books[index].data {
+                    self.book_form = Some(BookForm::new(Some(index), data.clone()));
+                    self.sale_form = None;
+                }
+            }
+
+            BookFormMessage::Save => {
+                let Some(form) = self.book_form.take() else {
+                    return Task::none();
+                };
+                let Some(data) = form.to_book_data() else {
+                    self.book_form = Some(form);
+                    return Task::none();
+                };
+
+                match form.book {
+                    Some(index) => {
+                        let book = &mut self.books[index];
+                        let before = book.data.replace(data);
+                        save_book_or_log(book);
+                        if let Some(before) = before {
+                            self.history.record(vec![BookChange::edited(book, before)]);
+                        }
+                        self.selected_book = Some(index);
+                    }
+                    None => match create_book(Path::new(&self.config.data_path), data) {
+                        Ok(book) => {
+                            self.history.record(vec![BookChange::created(&book)]);
+                            self.books.push(book);
+                            self.selected_book = Some(self.books.len() - 1);
+                        }
+                        Err(err) => {
+                            eprintln!("Failed to create book: {}", err);
+                            return Task::none();
+                        }
+                    },
+                }
+                return self.match_wants();
+            }
+
+            BookFormMessage::Cancel => {
+                self.book_form = None;
+            }
+
+            BookFormMessage::ContributorName(index, name) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.contributors[index].0 = name;
+                }
+            }
+
+            BookFormMessage::ContributorRole(index, role) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.contributors[index].1 = role;
+                }
+            }
+
+            BookFormMessage::AddContributor => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.contributors.push((String::new(), 0));
+                }
+            }
+
+            BookFormMessage::RemoveContributor(index) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.contributors.remove(index);
+                }
+            }
+
+            BookFormMessage::Title(title) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.title = title;
+                }
+            }
+
+            BookFormMessage::Year(year) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.year = year;
+                }
+            }
+
+            BookFormMessage::Publisher(publisher) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.publisher = publisher;
+                }
+            }
+
+            BookFormMessage::Isbn(isbn) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.isbn = isbn;
+                }
+            }
+
+            BookFormMessage::Pages(pages) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.pages = pages;
+                }
+            }
+
+            BookFormMessage::Format(format) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.format = Some(format);
+                }
+            }
+
+            BookFormMessage::Height(height) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.height = height;
+                }
+            }
+
+            BookFormMessage::Location(location) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.location = location
+                        .checked_sub(1)
+                        .map(|index| self.locations[index].name.clone())
+                        .unwrap_or_default();
+                }
+            }
+
+            BookFormMessage::Price(price) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.price = price;
+                }
+            }
+
+            BookFormMessage::PurchasePrice(purchase_price) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.purchase_price = purchase_price;
+                }
+            }
+
+            BookFormMessage::PurchaseDate(purchase_date) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.purchase_date = purchase_date;
+                }
+            }
+
+            BookFormMessage::DifferentialTaxation(ticked) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.differential_taxation = ticked;
+                }
+            }
+
+            BookFormMessage::Lot(lot) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.lot = lot.checked_sub(1).map(|index| self.lots[index].id);
+                }
+            }
+
+            BookFormMessage::Quantity(quantity) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.quantity = quantity;
+                }
+            }
+
+            BookFormMessage::Weight(weight) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.weight = weight;
+                }
+            }
+
+            BookFormMessage::Keywords(keywords) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.keywords = keywords;
+                }
+            }
+
+            BookFormMessage::Description(description) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.description = description;
+                }
+            }
+
+            BookFormMessage::PreviousOwners(previous_owners) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.previous_owners = previous_owners;
+                }
+            }
+
+            BookFormMessage::ExLibris(ex_libris) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.ex_libris = ex_libris;
+                }
+            }
+
+            BookFormMessage::Binding(binding) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.binding = binding.checked_sub(1).map(|index| Binding::ALL[index]);
+                }
+            }
+
+            BookFormMessage::BindingDetails(binding_details) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.binding_details = binding_details;
+                }
+            }
+
+            BookFormMessage::Illustrations(illustrations) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.illustrations = illustrations;
+                }
+            }
+
+            BookFormMessage::Collation(collation) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.collation = collation;
+                }
+            }
+
+            BookFormMessage::PrintingPlace(printing_place) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.printing_place = printing_place;
+                }
+            }
+
+            BookFormMessage::References(references) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.references = references;
+                }
+            }
+
+            BookFormMessage::Inscription(inscription) => {
+                if let Some(form) = self.book_form.as_mut() {
+                    form.data.inscription = inscription;
+                }
+            }
+        }
+        Task::none()
+    }
+}
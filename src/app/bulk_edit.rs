@@ -0,0 +1,325 @@
+// SPDX-License-Identifier: MIT
+
+use super::{AppModel, ContextPage, Message, field_label, status_label};
+use crate::book_data::parse_price;
+use crate::bulk::{BulkEdit, PriceChange, Skipped, bulk_edit};
+use crate::fl;
+use cosmic::app::context_drawer;
+use cosmic::prelude::*;
+use cosmic::widget;
+
+/// Messages of the bulk edit drawer.
+#[derive(Debug, Clone)]
+pub enum BulkEditMessage {
+    Open,
+    Location(usize),
+    Category(String),
+    Status(usize),
+    PriceMode(usize),
+    Price(String),
+    AddKeywords(String),
+    RemoveKeywords(String),
+    Apply,
+    Cancel,
+}
+
+/// Input state of the bulk edit drawer. Index 0 of each dropdown keeps the
+/// field unchanged.
+pub struct BulkEditForm {
+    /// Index into the location options: "unchanged", then the registered locations.
+    location: usize,
+    category: String,
+    /// Index into the status options: "unchanged", then `BulkEdit::STATUSES`.
+    status: usize,
+    /// Index into the price options: "unchanged", "set to" or "change by percent".
+    price_mode: usize,
+    price: String,
+    add_keywords: String,
+    remove_keywords: String,
+    location_options: Vec<String>,
+    status_options: Vec<String>,
+    price_options: Vec<String>,
+}
+
+impl BulkEditForm {
+    fn new(locations: &[String]) -> Self {
+        BulkEditForm {
+            location: 0,
+            category: String::new(),
+            status: 0,
+            price_mode: 0,
+            price: String::new(),
+            add_keywords: String::new(),
+            remove_keywords: String::new(),
+            location_options: std::iter::once(fl!("unchanged"))
+                .chain(locations.iter().cloned())
+                .collect(),
+            status_options: std::iter::once(fl!("unchanged"))
+                .chain(BulkEdit::STATUSES.into_iter().map(status_label))
+                .collect(),
+            price_options: vec![
+                fl!("unchanged"),
+                fl!("set-price"),
+                fl!("change-price-by-percent"),
+            ],
+        }
+    }
+
+    /// The edit described by the form, or `None` if an input is invalid.
+    fn to_edit(&self) -> Option<BulkEdit> {
+        let category = match self.category.trim() {
+            "" => None,
+            category => Some(category.parse().ok()?),
+        };
+        let price = match self.price_mode {
+            0 => None,
+            1 => Some(PriceChange::Set(parse_price(&self.price)?)),
+            // Lowering a price by more than all of it is a typo.
+            _ => Some(PriceChange::Percent(
+                self.price
+                    .trim()
+                    .trim_end_matches('%')
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|percent| *percent >= -100)?,
+            )),
+        };
+
+        Some(BulkEdit {
+            location: self
+                .location
+                .checked_sub(1)
+                .map(|_| self.location_options[self.location].clone()),
+            category,
+            price,
+            status: self
+                .status
+                .checked_sub(1)
+                .map(|index| BulkEdit::STATUSES[index]),
+            add_keywords: split_keywords(&self.add_keywords),
+            remove_keywords: split_keywords(&self.remove_keywords),
+        })
+    }
+}
+
+fn split_keywords(keywords: &str) -> Vec<String> {
+    keywords
+        .split(',')
+        .map(|keyword| keyword.trim().to_string())
+        .filter(|keyword| !keyword.is_empty())
+        .collect()
+}
+
+impl AppModel {
+    pub(super) fn bulk_edit_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Message>> {
+        let form = self.bulk_edit_form.as_ref()?;
+        let edit = form.to_edit();
+
+        // Shows what the edit does to every selected book before it is applied.
+        let preview = edit.as_ref().filter(|edit| !edit.is_empty()).map(|edit| {
+            self.book_selection.iter().fold(
+                widget::settings::section().title(fl!("preview")),
+                |section, index| {
+                    let book = &self.books[*index];
+                    let Some(data) = book.data.as_ref() else {
+                        return section;
+                    };
+                    let changes = match edit.apply(data) {
+                        Ok(edited) => {
+                            let changes = edited.changes_from(data);
+                            if changes.is_empty() {
+                                fl!("no-changes")
+                            } else {
+                                changes
+                                    .iter()
+                                    .map(|change| {
+                                        format!(
+                                            "{}: {} → {}",
+                                            field_label(&change.field),
+                                            change.old,
+                                            change.new
+                                        )
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            }
+                        }
+                        Err(Skipped::Status) => fl!("status-not-allowed"),
+                        Err(Skipped::Price) => fl!("price-not-applicable"),
+                    };
+                    section.add(widget::settings::item(
+                        format!("{:05} {}", book.id, data.title),
+                        widget::text(changes),
+                    ))
+                },
+            )
+        });
+
+        let mut apply = widget::button::suggested(fl!("apply"));
+        if edit.is_some_and(|edit| !edit.is_empty()) && !self.book_selection.is_empty() {
+            apply = apply.on_press(Message::BulkEdit(BulkEditMessage::Apply));
+        }
+
+        let mut fields = widget::settings::section()
+            .add(widget::settings::item(
+                fl!("location"),
+                widget::dropdown(&form.location_options, Some(form.location), |location| {
+                    Message::BulkEdit(BulkEditMessage::Location(location))
+                }),
+            ))
+            .add(widget::settings::item(
+                fl!("category"),
+                widget::text_input(fl!("unchanged"), &form.category)
+                    .on_input(|category| Message::BulkEdit(BulkEditMessage::Category(category))),
+            ))
+            .add(widget::settings::item(
+                fl!("status"),
+                widget::dropdown(&form.status_options, Some(form.status), |status| {
+                    Message::BulkEdit(BulkEditMessage::Status(status))
+                }),
+            ))
+            .add(widget::settings::item(
+                fl!("price-change"),
+                widget::dropdown(&form.price_options, Some(form.price_mode), |mode| {
+                    Message::BulkEdit(BulkEditMessage::PriceMode(mode))
+                }),
+            ));
+        if form.price_mode > 0 {
+            let placeholder = match form.price_mode {
+                1 => "0,00",
+                _ => "+10 %",
+            };
+            fields = fields.add(widget::settings::item(
+                fl!("price"),
+                widget::text_input(placeholder, &form.price)
+                    .on_input(|price| Message::BulkEdit(BulkEditMessage::Price(price))),
+            ));
+        }
+        fields = fields
+            .add(widget::settings::item(
+                fl!("add-keywords"),
+                widget::text_input(fl!("comma-separated"), &form.add_keywords)
+                    .on_input(|keywords| Message::BulkEdit(BulkEditMessage::AddKeywords(keywords))),
+            ))
+            .add(widget::settings::item(
+                fl!("remove-keywords"),
+                widget::text_input(fl!("comma-separated"), &form.remove_keywords).on_input(
+                    |keywords| Message::BulkEdit(BulkEditMessage::RemoveKeywords(keywords)),
+                ),
+            ));
+
+        let content = widget::column()
+            .push(fields)
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::BulkEdit(BulkEditMessage::Cancel)),
+                    )
+                    .push(apply)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .push_maybe(preview)
+            .spacing(Theme::default().cosmic().space_s());
+
+        Some(
+            context_drawer::context_drawer(
+                content,
+                Message::ToggleContextPage(ContextPage::BulkEdit),
+            )
+            .title(fl!("bulk-edit-title", count = self.book_selection.len())),
+        )
+    }
+
+    pub(super) fn update_bulk_edit(
+        &mut self,
+        message: BulkEditMessage,
+    ) -> Task<cosmic::Action<Message>> {
+        match message {
+            BulkEditMessage::Open => {
+                let locations: Vec<String> = self
+                    .locations
+                    .iter()
+                    .map(|location| location.name.clone())
+                    .collect();
+                self.bulk_edit_form = Some(BulkEditForm::new(&locations));
+                self.context_page = ContextPage::BulkEdit;
+                self.core.window.show_context = true;
+            }
+
+            BulkEditMessage::Location(location) => {
+                if let Some(form) = self.bulk_edit_form.as_mut() {
+                    form.location = location;
+                }
+            }
+
+            BulkEditMessage::Category(category) => {
+                if let Some(form) = self.bulk_edit_form.as_mut() {
+                    form.category = category;
+                }
+            }
+
+            BulkEditMessage::Status(status) => {
+                if let Some(form) = self.bulk_edit_form.as_mut() {
+                    form.status = status;
+                }
+            }
+
+            BulkEditMessage::PriceMode(mode) => {
+                if let Some(form) = self.bulk_edit_form.as_mut() {
+                    form.price_mode = mode;
+                }
+            }
+
+            BulkEditMessage::Price(price) => {
+                if let Some(form) = self.bulk_edit_form.as_mut() {
+                    form.price = price;
+                }
+            }
+
+            BulkEditMessage::AddKeywords(keywords) => {
+                if let Some(form) = self.bulk_edit_form.as_mut() {
+                    form.add_keywords = keywords;
+                }
+            }
+
+            BulkEditMessage::RemoveKeywords(keywords) => {
+                if let Some(form) = self.bulk_edit_form.as_mut() {
+                    form.remove_keywords = keywords;
+                }
+            }
+
+            BulkEditMessage::Apply => {
+                let Some(edit) = self.bulk_edit_form.as_ref().and_then(BulkEditForm::to_edit)
+                else {
+                    return Task::none();
+                };
+                let (changes, failed) = bulk_edit(&mut self.books, &self.book_selection, &edit);
+                if failed > 0 {
+                    eprintln!("Failed to save {failed} books");
+                }
+                self.bulk_edit_form = None;
+                self.core.window.show_context = false;
+
+                let count = changes.len();
+                if count == 0 {
+                    return Task::none();
+                }
+                self.history.record(changes);
+                let toast = widget::toaster::Toast::new(fl!("books-changed", count = count))
+                    .action(fl!("undo"), |_| Message::Undo);
+                return Task::batch([
+                    self.toasts.push(toast).map(cosmic::Action::App),
+                    self.match_wants(),
+                ]);
+            }
+
+            BulkEditMessage::Cancel => {
+                self.bulk_edit_form = None;
+                self.core.window.show_context = false;
+            }
+        }
+        Task::none()
+    }
+}
@@ -0,0 +1,583 @@
+// SPDX-License-Identifier: MIT
+
+use super::{AppModel, ContextPage, Message};
+use crate::book_data::{format_price, parse_price};
+use crate::customer::{
+    Customer, Want, WantMatch, find_want_matches, save_customer, save_want_matches,
+};
+use crate::fl;
+use cosmic::app::context_drawer;
+use cosmic::iced::Length;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget::{self, container, scrollable};
+use std::path::Path;
+
+/// Messages of the Customers page and the customer drawer.
+#[derive(Debug, Clone)]
+pub enum CustomerMessage {
+    Select(usize),
+    New,
+    Edit,
+    Name(String),
+    Email(String),
+    Address(usize, String),
+    AddAddress,
+    Notes(String),
+    WantAuthor(usize, String),
+    WantTitle(usize, String),
+    WantKeywords(usize, String),
+    WantMaxPrice(usize, String),
+    AddWant,
+    RemoveWant(usize),
+    Save,
+    Cancel,
+    DismissMatch(usize),
+}
+
+/// Input state of the customer form.
+pub struct CustomerForm {
+    customer: Customer,
+    wants: Vec<WantInput>,
+}
+
+/// Input state of one want-list entry in the customer form.
+#[derive(Default)]
+struct WantInput {
+    author: String,
+    title: String,
+    /// Comma separated.
+    keywords: String,
+    max_price: String,
+}
+
+impl CustomerForm {
+    fn new(customer: Customer) -> Self {
+        let wants = customer
+            .want_list
+            .iter()
+            .map(|want| WantInput {
+                author: want.author.clone(),
+                title: want.title.clone(),
+                keywords: want.keywords.join(", "),
+                max_price: want
+                    .max_price
+                    .map(|price| format_price(price).trim_end_matches(" €").to_string())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        CustomerForm { customer, wants }
+    }
+
+    /// Builds the customer described by the form, or `None` if an input is invalid.
+    fn to_customer(&self) -> Option<Customer> {
+        let mut customer = self.customer.clone();
+        customer.name = customer.name.trim().to_string();
+        if customer.name.is_empty() {
+            return None;
+        }
+        customer
+            .addresses
+            .retain(|address| !address.trim().is_empty());
+
+        customer.want_list = vec![];
+        for input in &self.wants {
+            let want = Want {
+                author: input.author.trim().to_string(),
+                title: input.title.trim().to_string(),
+                keywords: input
+                    .keywords
+                    .split(',')
+                    .map(|keyword| keyword.trim().to_string())
+                    .filter(|keyword| !keyword.is_empty())
+                    .collect(),
+                max_price: match input.max_price.trim() {
+                    "" => None,
+                    max_price => Some(parse_price(max_price)?),
+                },
+            };
+            if !want.is_empty() {
+                customer.want_list.push(want);
+            }
+        }
+
+        Some(customer)
+    }
+}
+
+impl AppModel {
+    pub(super) fn customers_view(&self) -> Element<'_, Message> {
+        let space_s = cosmic::theme::spacing().space_s;
+
+        let mut customers: Vec<_> = self
+            .customers
+            .iter()
+            .enumerate()
+            .map(|(index, customer)| {
+                container(
+                    widget::mouse_area(
+                        widget::row()
+                            .push(widget::text::heading(&customer.name))
+                            .push(widget::horizontal_space())
+                            .push(widget::text(&customer.email))
+                            .align_y(Vertical::Center)
+                            .width(Length::Fill),
+                    )
+                    .on_press(Message::Customer(CustomerMessage::Select(index))),
+                )
+                .width(Length::Fill)
+                .height(Theme::default().cosmic().space_xl())
+            })
+            .flat_map(|item| [container(widget::divider::horizontal::default()), item])
+            .collect();
+
+        if !customers.is_empty() {
+            customers.remove(0);
+        }
+
+        let pending =
+            self.want_matches
+                .iter()
+                .enumerate()
+                .filter(|(_, want_match)| !want_match.dismissed)
+                .filter_map(|(index, want_match)| {
+                    let customer = self
+                        .customers
+                        .iter()
+                        .find(|customer| customer.id == want_match.customer_id)?;
+                    let book = self
+                        .books
+                        .iter()
+                        .position(|book| book.id == want_match.book_id)?;
+                    Some((index, customer, book))
+                })
+                .fold(
+                    widget::settings::section().title(fl!("pending-matches")),
+                    |section, (index, customer, book)| {
+                        let title = self.books[book].data.as_ref().unwrap().title.clone();
+                        section.add(widget::settings::item(
+                            format!("{}: {}", customer.name, title),
+                            widget::row()
+                                .push(
+                                    widget::button::text(fl!("show-book"))
+                                        .on_press(Message::SelectBook(book)),
+                                )
+                                .push(widget::button::text(fl!("dismiss")).on_press(
+                                    Message::Customer(CustomerMessage::DismissMatch(index)),
+                                )),
+                        ))
+                    },
+                );
+
+        widget::column::with_capacity(3)
+            .push_maybe(
+                self.want_matches
+                    .iter()
+                    .any(|want_match| !want_match.dismissed)
+                    .then_some(pending),
+            )
+            .push(
+                widget::row().push(widget::horizontal_space()).push(
+                    widget::button::standard(fl!("new-customer"))
+                        .on_press(Message::Customer(CustomerMessage::New)),
+                ),
+            )
+            .push(scrollable(widget::column().append(&mut customers)))
+            .spacing(space_s)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub(super) fn customer_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Message>> {
+        let on_close = Message::ToggleContextPage(ContextPage::CustomerDetail);
+
+        if let Some(form) = &self.customer_form {
+            return Some(
+                context_drawer::context_drawer(self.customer_form_view(form), on_close)
+                    .title(fl!("edit-customer")),
+            );
+        }
+
+        let customer = self.customers.get(self.selected_customer?)?;
+        Some(
+            context_drawer::context_drawer(self.customer_detail(customer), on_close)
+                .title(customer.name.clone()),
+        )
+    }
+
+    fn customer_detail<'a>(&'a self, customer: &'a Customer) -> Element<'a, Message> {
+        let contact = customer.addresses.iter().fold(
+            widget::settings::section()
+                .add(widget::settings::item(
+                    fl!("email"),
+                    widget::text(&customer.email),
+                ))
+                .add(widget::settings::item(
+                    fl!("notes"),
+                    widget::text(&customer.notes),
+                )),
+            |section, address| {
+                section.add(widget::settings::item(
+                    fl!("address"),
+                    widget::text(address),
+                ))
+            },
+        );
+
+        let want_list = customer.want_list.iter().fold(
+            widget::settings::section().title(fl!("want-list")),
+            |section, want| {
+                let mut wanted = [want.author.as_str(), want.title.as_str()]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(": ");
+                if !want.keywords.is_empty() {
+                    wanted.push_str(&format!(" ({})", want.keywords.join(", ")));
+                }
+                let max_price = want.max_price.map(format_price).unwrap_or_default();
+
+                section.add(widget::settings::item(wanted, widget::text(max_price)))
+            },
+        );
+
+        let purchases = self
+            .sales
+            .iter()
+            .filter(|sale| sale.customer_id == Some(customer.id))
+            .rev()
+            .fold(
+                widget::settings::section().title(fl!("purchase-history")),
+                |section, sale| {
+                    let titles = sale
+                        .items
+                        .iter()
+                        .map(|item| {
+                            self.books
+                                .iter()
+                                .find(|book| book.id == item.book_id)
+                                .and_then(|book| book.data.as_ref())
+                                .map_or(format!("{:05}", item.book_id), |data| data.title.clone())
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    section.add(widget::settings::item(
+                        format!("{}: {}", sale.date, titles),
+                        widget::text(format_price(sale.revenue() + sale.shipping)),
+                    ))
+                },
+            );
+
+        widget::column()
+            .push(contact)
+            .push(
+                widget::button::standard(fl!("edit"))
+                    .on_press(Message::Customer(CustomerMessage::Edit)),
+            )
+            .push(want_list)
+            .push(purchases)
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    fn customer_form_view<'a>(&'a self, form: &'a CustomerForm) -> Element<'a, Message> {
+        let customer = &form.customer;
+
+        let addresses = customer.addresses.iter().enumerate().fold(
+            widget::settings::section().title(fl!("addresses")),
+            |section, (index, address)| {
+                section.add(widget::settings::item(
+                    fl!("address"),
+                    widget::text_input("", address).on_input(move |address| {
+                        Message::Customer(CustomerMessage::Address(index, address))
+                    }),
+                ))
+            },
+        );
+
+        let wants = form.wants.iter().enumerate().fold(
+            widget::column().spacing(Theme::default().cosmic().space_xxs()),
+            |column, (index, want)| {
+                column.push(
+                    widget::settings::section()
+                        .title(fl!("want"))
+                        .add(widget::settings::item(
+                            fl!("author"),
+                            widget::text_input("", &want.author).on_input(move |author| {
+                                Message::Customer(CustomerMessage::WantAuthor(index, author))
+                            }),
+                        ))
+                        .add(widget::settings::item(
+                            fl!("title"),
+                            widget::text_input("", &want.title).on_input(move |title| {
+                                Message::Customer(CustomerMessage::WantTitle(index, title))
+                            }),
+                        ))
+                        .add(widget::settings::item(
+                            fl!("keywords"),
+                            widget::text_input(fl!("comma-separated"), &want.keywords).on_input(
+                                move |keywords| {
+                                    Message::Customer(CustomerMessage::WantKeywords(
+                                        index, keywords,
+                                    ))
+                                },
+                            ),
+                        ))
+                        .add(widget::settings::item(
+                            fl!("max-price"),
+                            widget::text_input(fl!("optional"), &want.max_price).on_input(
+                                move |max_price| {
+                                    Message::Customer(CustomerMessage::WantMaxPrice(
+                                        index, max_price,
+                                    ))
+                                },
+                            ),
+                        ))
+                        .add(
+                            widget::button::destructive(fl!("remove"))
+                                .on_press(Message::Customer(CustomerMessage::RemoveWant(index))),
+                        ),
+                )
+            },
+        );
+
+        let mut save = widget::button::suggested(fl!("save"));
+        if form.to_customer().is_some() {
+            save = save.on_press(Message::Customer(CustomerMessage::Save));
+        }
+
+        widget::column()
+            .push(
+                widget::settings::section()
+                    .add(widget::settings::item(
+                        fl!("name"),
+                        widget::text_input("", &customer.name)
+                            .on_input(|name| Message::Customer(CustomerMessage::Name(name))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("email"),
+                        widget::text_input("", &customer.email)
+                            .on_input(|email| Message::Customer(CustomerMessage::Email(email))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("notes"),
+                        widget::text_input("", &customer.notes)
+                            .on_input(|notes| Message::Customer(CustomerMessage::Notes(notes))),
+                    )),
+            )
+            .push(addresses)
+            .push(
+                widget::button::standard(fl!("add-address"))
+                    .on_press(Message::Customer(CustomerMessage::AddAddress)),
+            )
+            .push(wants)
+            .push(
+                widget::button::standard(fl!("add-want"))
+                    .on_press(Message::Customer(CustomerMessage::AddWant)),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::Customer(CustomerMessage::Cancel)),
+                    )
+                    .push(save)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    pub(super) fn update_customer(
+        &mut self,
+        message: CustomerMessage,
+    ) -> Task<cosmic::Action<Message>> {
+        match message {
+            CustomerMessage::Select(index) => {
+                self.selected_customer = Some(index);
+                self.customer_form = None;
+                self.context_page = ContextPage::CustomerDetail;
+                self.core.window.show_context = true;
+            }
+
+            CustomerMessage::New => {
+                self.customer_form = Some(CustomerForm::new(Customer::default()));
+                self.context_page = ContextPage::CustomerDetail;
+                self.core.window.show_context = true;
+            }
+
+            CustomerMessage::Edit => {
+                if let Some(customer) = self.selected_customer.map(|index| &self.customers[index]) {
+                    self.customer_form = Some(CustomerForm::new(customer.clone()));
+                }
+            }
+
+            CustomerMessage::Name(name) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.customer.name = name;
+                }
+            }
+
+            CustomerMessage::Email(email) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.customer.email = email;
+                }
+            }
+
+            CustomerMessage::Address(index, address) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.customer.addresses[index] = address;
+                }
+            }
+
+            CustomerMessage::AddAddress => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.customer.addresses.push(String::new());
+                }
+            }
+
+            CustomerMessage::Notes(notes) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.customer.notes = notes;
+                }
+            }
+
+            CustomerMessage::WantAuthor(index, author) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.wants[index].author = author;
+                }
+            }
+
+            CustomerMessage::WantTitle(index, title) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.wants[index].title = title;
+                }
+            }
+
+            CustomerMessage::WantKeywords(index, keywords) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.wants[index].keywords = keywords;
+                }
+            }
+
+            CustomerMessage::WantMaxPrice(index, max_price) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.wants[index].max_price = max_price;
+                }
+            }
+
+            CustomerMessage::AddWant => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.wants.push(WantInput::default());
+                }
+            }
+
+            CustomerMessage::RemoveWant(index) => {
+                if let Some(form) = self.customer_form.as_mut() {
+                    form.wants.remove(index);
+                }
+            }
+
+            CustomerMessage::Save => {
+                let Some(mut customer) = self
+                    .customer_form
+                    .as_ref()
+                    .and_then(|form| form.to_customer())
+                else {
+                    return Task::none();
+                };
+                self.customer_form = None;
+
+                let data_path = Path::new(&self.config.data_path);
+                if let Err(err) = save_customer(data_path, &mut customer) {
+                    eprintln!("Failed to save customer {}: {}", customer.name, err);
+                    return Task::none();
+                }
+
+                match self.customers.iter().position(|c| c.id == customer.id) {
+                    Some(index) => {
+                        self.customers[index] = customer;
+                        self.selected_customer = Some(index);
+                    }
+                    None => {
+                        self.customers.push(customer);
+                        self.selected_customer = Some(self.customers.len() - 1);
+                    }
+                }
+                self.refresh_customer_options();
+                return self.match_wants();
+            }
+
+            CustomerMessage::Cancel => {
+                self.customer_form = None;
+            }
+
+            CustomerMessage::DismissMatch(index) => {
+                self.want_matches[index].dismissed = true;
+                self.save_want_matches();
+            }
+        }
+        Task::none()
+    }
+
+    /// Matches all want-lists against the stock and announces books that match
+    /// for the first time.
+    pub(super) fn match_wants(&mut self) -> Task<cosmic::Action<Message>> {
+        let found = find_want_matches(&self.customers, &self.books);
+        let is_same =
+            |a: &WantMatch, b: &WantMatch| a.customer_id == b.customer_id && a.book_id == b.book_id;
+
+        let mut tasks = vec![];
+        let mut matches = Vec::with_capacity(found.len());
+        for want_match in found {
+            match self
+                .want_matches
+                .iter()
+                .find(|known| is_same(known, &want_match))
+            {
+                Some(known) => matches.push(known.clone()),
+                None => {
+                    let customer = self
+                        .customers
+                        .iter()
+                        .find(|customer| customer.id == want_match.customer_id)
+                        .unwrap();
+                    let book = self
+                        .books
+                        .iter()
+                        .find(|book| book.id == want_match.book_id)
+                        .unwrap();
+                    let toast = widget::toaster::Toast::new(fl!(
+                        "want-list-match",
+                        customer = customer.name.clone(),
+                        title = book.data.as_ref().unwrap().title.clone()
+                    ));
+                    tasks.push(self.toasts.push(toast).map(cosmic::Action::App));
+                    matches.push(want_match);
+                }
+            }
+        }
+
+        if matches != self.want_matches {
+            self.want_matches = matches;
+            self.save_want_matches();
+        }
+        Task::batch(tasks)
+    }
+
+    fn save_want_matches(&self) {
+        let data_path = Path::new(&self.config.data_path);
+        if let Err(err) = save_want_matches(data_path, &self.want_matches) {
+            eprintln!("Failed to save want-list matches: {}", err);
+        }
+    }
+
+    /// Rebuilds the labels of the customer dropdown in the sale form.
+    pub(super) fn refresh_customer_options(&mut self) {
+        self.customer_options = std::iter::once(fl!("no-customer"))
+            .chain(self.customers.iter().map(|customer| customer.name.clone()))
+            .collect();
+    }
+}
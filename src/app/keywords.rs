@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: MIT
+
+use super::{AppModel, ContextPage, Message, Page};
+use crate::fl;
+use crate::keyword::{keyword_counts, merge_keywords, variants};
+use cosmic::app::context_drawer;
+use cosmic::iced::Length;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget::{self, scrollable};
+
+/// Font sizes of the least and the most used keywords in the tag cloud.
+const CLOUD_SIZES: (u16, u16) = (12, 28);
+
+/// Messages of the Keywords page and the keyword drawer.
+#[derive(Debug, Clone)]
+pub enum KeywordMessage {
+    Select(String),
+    /// Lists the books with the keyword on the Books page.
+    Filter(String),
+    ClearFilter,
+    RenameInput(String),
+    Rename,
+    /// Index into the keyword options.
+    Target(usize),
+    /// Merges the selected keyword into the target.
+    Merge,
+    /// Merges the given keyword into the selected one.
+    MergeVariant(String),
+}
+
+impl AppModel {
+    pub(super) fn keywords_view(&self) -> Element<'_, Message> {
+        let counts = keyword_counts(&self.books);
+        let most = counts.iter().map(|(_, count)| *count).max().unwrap_or(1);
+        let (smallest, largest) = CLOUD_SIZES;
+
+        let chips: Vec<Element<_>> = counts
+            .into_iter()
+            .map(|(keyword, count)| {
+                let size = smallest + ((largest - smallest) as usize * count / most) as u16;
+                widget::button::custom(widget::text(format!("{keyword} ({count})")).size(size))
+                    .class(cosmic::theme::Button::Text)
+                    .on_press(Message::Keyword(KeywordMessage::Select(keyword)))
+                    .into()
+            })
+            .collect();
+
+        widget::column()
+            .push(widget::text::caption(fl!("keyword-cloud-hint")))
+            .push(scrollable(
+                widget::flex_row(chips)
+                    .row_spacing(Theme::default().cosmic().space_xxs())
+                    .column_spacing(Theme::default().cosmic().space_xxs())
+                    .width(Length::Fill),
+            ))
+            .spacing(cosmic::theme::spacing().space_s)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub(super) fn keyword_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Message>> {
+        let keyword = self.selected_keyword.as_ref()?;
+        let counts = keyword_counts(&self.books);
+        let count = counts
+            .iter()
+            .find(|(known, _)| known == keyword)
+            .map_or(0, |(_, count)| *count);
+        let space_xxs = Theme::default().cosmic().space_xxs();
+
+        let mut rename = widget::button::standard(fl!("rename"));
+        let name = self.keyword_rename.trim();
+        if !name.is_empty() && name != keyword && !name.contains(',') {
+            rename = rename.on_press(Message::Keyword(KeywordMessage::Rename));
+        }
+
+        let mut merge = widget::button::standard(fl!("merge-into"));
+        if self.keyword_target.is_some() {
+            merge = merge.on_press(Message::Keyword(KeywordMessage::Merge));
+        }
+
+        let variants = variants(&counts, keyword);
+        let variants = (!variants.is_empty()).then(|| {
+            variants.into_iter().fold(
+                widget::settings::section().title(fl!("similar-keywords")),
+                |section, variant| {
+                    section.add(widget::settings::item(
+                        variant.to_string(),
+                        widget::button::text(fl!("merge-here")).on_press(Message::Keyword(
+                            KeywordMessage::MergeVariant(variant.to_string()),
+                        )),
+                    ))
+                },
+            )
+        });
+
+        let content = widget::column()
+            .push(widget::settings::section().add(widget::settings::item(
+                fl!("books"),
+                widget::text(count.to_string()),
+            )))
+            .push(
+                widget::button::standard(fl!("show-books"))
+                    .on_press(Message::Keyword(KeywordMessage::Filter(keyword.clone()))),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::text_input(fl!("new-name"), &self.keyword_rename)
+                            .on_input(|name| Message::Keyword(KeywordMessage::RenameInput(name))),
+                    )
+                    .push(rename)
+                    .align_y(Vertical::Center)
+                    .spacing(space_xxs),
+            )
+            .push(
+                widget::row()
+                    .push(widget::dropdown(
+                        &self.keyword_options,
+                        self.keyword_target,
+                        |target| Message::Keyword(KeywordMessage::Target(target)),
+                    ))
+                    .push(merge)
+                    .align_y(Vertical::Center)
+                    .spacing(space_xxs),
+            )
+            .push_maybe(variants)
+            .spacing(Theme::default().cosmic().space_s());
+
+        Some(
+            context_drawer::context_drawer(
+                content,
+                Message::ToggleContextPage(ContextPage::KeywordDetail),
+            )
+            .title(keyword.clone()),
+        )
+    }
+
+    pub(super) fn update_keyword(
+        &mut self,
+        message: KeywordMessage,
+    ) -> Task<cosmic::Action<Message>> {
+        match message {
+            KeywordMessage::Select(keyword) => {
+                self.select_keyword(keyword);
+                self.context_page = ContextPage::KeywordDetail;
+                self.core.window.show_context = true;
+            }
+
+            KeywordMessage::Filter(keyword) => {
+                self.keyword_filter = Some(keyword);
+                self.core.window.show_context = false;
+                let books = self
+                    .nav
+                    .iter()
+                    .find(|id| matches!(self.nav.data::<Page>(*id), Some(Page::Books)));
+                if let Some(id) = books {
+                    self.nav.activate(id);
+                    return self.update_title();
+                }
+            }
+
+            KeywordMessage::ClearFilter => {
+                self.keyword_filter = None;
+            }
+
+            KeywordMessage::RenameInput(name) => {
+                self.keyword_rename = name;
+            }
+
+            KeywordMessage::Rename => {
+                let target = self.keyword_rename.trim().to_string();
+                return self.merge_selected_keyword(target);
+            }
+
+            KeywordMessage::Target(target) => {
+                self.keyword_target = Some(target);
+            }
+
+            KeywordMessage::Merge => {
+                if let Some(target) = self.keyword_target {
+                    let target = self.keyword_options[target].clone();
+                    return self.merge_selected_keyword(target);
+                }
+            }
+
+            KeywordMessage::MergeVariant(variant) => {
+                if let Some(keyword) = self.selected_keyword.clone() {
+                    let (_, failed) = self
+                        .record_edits(|app| merge_keywords(&mut app.books, &[variant], &keyword));
+                    self.select_keyword(keyword);
+                    return Task::batch([self.report_unsaved(failed), self.match_wants()]);
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Shows `keyword` in the keyword drawer, with the other keywords as merge
+    /// targets.
+    fn select_keyword(&mut self, keyword: String) {
+        self.keyword_rename = keyword.clone();
+        self.keyword_target = None;
+        self.keyword_options = keyword_counts(&self.books)
+            .into_iter()
+            .map(|(other, _)| other)
+            .filter(|other| *other != keyword)
+            .collect();
+        self.selected_keyword = Some(keyword);
+    }
+
+    /// Replaces the selected keyword with `target` in all books, which merges
+    /// them if `target` is in use already.
+    fn merge_selected_keyword(&mut self, target: String) -> Task<cosmic::Action<Message>> {
+        let Some(keyword) = self.selected_keyword.clone() else {
+            return Task::none();
+        };
+        let (_, failed) =
+            self.record_edits(|app| merge_keywords(&mut app.books, &[keyword.clone()], &target));
+        if self.keyword_filter.as_ref() == Some(&keyword) {
+            self.keyword_filter = Some(target.clone());
+        }
+        self.select_keyword(target);
+        Task::batch([self.report_unsaved(failed), self.match_wants()])
+    }
+}
@@ -0,0 +1,460 @@
+// SPDX-License-Identifier: MIT
+
+use super::stocktake::AuditMessage;
+use super::{AppModel, ContextPage, Message};
+use crate::fl;
+use crate::location::{
+    Location, LocationKind, find_location, move_books, rename_location, save_locations,
+};
+use cosmic::app::context_drawer;
+use cosmic::iced::Length;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget::{self, container, scrollable};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Messages of the Locations page and the location drawer.
+#[derive(Debug, Clone)]
+pub enum LocationMessage {
+    Select(usize),
+    New,
+    /// Adds a location that books refer to but the register lacks.
+    Register(String),
+    Name(String),
+    Kind(usize),
+    Parent(usize),
+    Save,
+    Cancel,
+    ToggleBook(usize, bool),
+    Target(usize),
+    MoveSelected,
+    RenameInput(String),
+    Rename,
+}
+
+/// Input state of the form for new locations.
+pub struct LocationForm {
+    name: String,
+    kind: usize,
+    /// Index into the location options, 0 for none.
+    parent: usize,
+}
+
+impl AppModel {
+    pub(super) fn locations_view(&self) -> Element<'_, Message> {
+        let space_s = cosmic::theme::spacing().space_s;
+
+        let mut locations: Vec<_> = self
+            .location_tree()
+            .into_iter()
+            .map(|(depth, index)| {
+                let location = &self.locations[index];
+                let count = self.books_at(location).len();
+                container(
+                    widget::mouse_area(
+                        widget::row()
+                            .push(widget::Space::with_width(
+                                Theme::default().cosmic().space_l() * depth as u16,
+                            ))
+                            .push(widget::text::heading(&location.name))
+                            .push(widget::text(location_kind_label(location.kind)))
+                            .push(widget::horizontal_space())
+                            .push(widget::text(fl!("book-count", count = count)))
+                            .align_y(Vertical::Center)
+                            .spacing(space_s)
+                            .width(Length::Fill),
+                    )
+                    .on_press(Message::Location(LocationMessage::Select(index))),
+                )
+                .width(Length::Fill)
+                .height(Theme::default().cosmic().space_xl())
+            })
+            .flat_map(|item| [container(widget::divider::horizontal::default()), item])
+            .collect();
+
+        if !locations.is_empty() {
+            locations.remove(0);
+        }
+
+        let unregistered = self.unregistered_locations();
+        let unregistered = (!unregistered.is_empty()).then(|| {
+            unregistered.into_iter().fold(
+                widget::settings::section().title(fl!("unregistered-locations")),
+                |section, (location, count)| {
+                    section.add(widget::settings::item(
+                        format!("{location} ({count})"),
+                        widget::button::text(fl!("register"))
+                            .on_press(Message::Location(LocationMessage::Register(location))),
+                    ))
+                },
+            )
+        });
+
+        widget::column()
+            .push(
+                widget::row().push(widget::horizontal_space()).push(
+                    widget::button::standard(fl!("new-location"))
+                        .on_press(Message::Location(LocationMessage::New)),
+                ),
+            )
+            .push_maybe(unregistered)
+            .push(scrollable(widget::column().append(&mut locations)))
+            .spacing(space_s)
+            .height(Length::Fill)
+            .into()
+    }
+
+    pub(super) fn location_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Message>> {
+        let on_close = Message::ToggleContextPage(ContextPage::LocationDetail);
+
+        if let Some(form) = &self.location_form {
+            return Some(
+                context_drawer::context_drawer(self.location_form_view(form), on_close)
+                    .title(fl!("new-location")),
+            );
+        }
+
+        let location = self.locations.get(self.selected_location?)?;
+        Some(
+            context_drawer::context_drawer(self.location_detail(location), on_close)
+                .title(location.name.clone()),
+        )
+    }
+
+    fn location_detail<'a>(&'a self, location: &'a Location) -> Element<'a, Message> {
+        let space_xxs = Theme::default().cosmic().space_xxs();
+
+        let books = self.books_at(location).into_iter().fold(
+            widget::settings::section().title(fl!("books")),
+            |section, index| {
+                let book = &self.books[index];
+                let title = book
+                    .data
+                    .as_ref()
+                    .map(|data| data.title.clone())
+                    .unwrap_or_default();
+                section.add(
+                    widget::checkbox(
+                        format!("{:05} {}", book.id, title),
+                        self.location_selection.contains(&index),
+                    )
+                    .on_toggle(move |ticked| {
+                        Message::Location(LocationMessage::ToggleBook(index, ticked))
+                    }),
+                )
+            },
+        );
+
+        let mut move_button = widget::button::standard(fl!("move-selected"));
+        if !self.location_selection.is_empty() && self.location_target.is_some() {
+            move_button = move_button.on_press(Message::Location(LocationMessage::MoveSelected));
+        }
+
+        let mut rename_button = widget::button::standard(fl!("rename"));
+        let name = self.location_rename.trim();
+        // Changing only case or spacing of the name is a rename, too.
+        if !name.is_empty()
+            && name != location.name
+            && find_location(&self.locations, name)
+                .is_none_or(|index| Some(index) == self.selected_location)
+        {
+            rename_button = rename_button.on_press(Message::Location(LocationMessage::Rename));
+        }
+
+        // Only one stocktake runs at a time; it can be reopened from any location.
+        let stocktaking = match &self.audit {
+            Some(audit) => {
+                widget::button::standard(fl!("open-stocktaking", location = audit.location.clone()))
+                    .on_press(Message::ToggleContextPage(ContextPage::Audit))
+            }
+            None => widget::button::standard(fl!("start-stocktaking"))
+                .on_press(Message::Audit(AuditMessage::Start)),
+        };
+
+        widget::column()
+            .push(
+                widget::settings::section()
+                    .add(widget::settings::item(
+                        fl!("location-kind"),
+                        widget::text(location_kind_label(location.kind)),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("part-of"),
+                        widget::text(&location.parent),
+                    )),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::text_input(fl!("new-name"), &self.location_rename)
+                            .on_input(|name| Message::Location(LocationMessage::RenameInput(name))),
+                    )
+                    .push(rename_button)
+                    .align_y(Vertical::Center)
+                    .spacing(space_xxs),
+            )
+            .push(stocktaking)
+            .push(books)
+            .push(
+                widget::row()
+                    .push(widget::dropdown(
+                        &self.location_options[1..],
+                        self.location_target,
+                        |target| Message::Location(LocationMessage::Target(target)),
+                    ))
+                    .push(move_button)
+                    .align_y(Vertical::Center)
+                    .spacing(space_xxs),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    fn location_form_view<'a>(&'a self, form: &'a LocationForm) -> Element<'a, Message> {
+        let mut save = widget::button::suggested(fl!("save"));
+        let name = form.name.trim();
+        if !name.is_empty() && find_location(&self.locations, name).is_none() {
+            save = save.on_press(Message::Location(LocationMessage::Save));
+        }
+
+        widget::column()
+            .push(
+                widget::settings::section()
+                    .add(widget::settings::item(
+                        fl!("name"),
+                        widget::text_input("", &form.name)
+                            .on_input(|name| Message::Location(LocationMessage::Name(name))),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("location-kind"),
+                        widget::dropdown(&self.location_kind_options, Some(form.kind), |kind| {
+                            Message::Location(LocationMessage::Kind(kind))
+                        }),
+                    ))
+                    .add(widget::settings::item(
+                        fl!("part-of"),
+                        widget::dropdown(&self.location_options, Some(form.parent), |parent| {
+                            Message::Location(LocationMessage::Parent(parent))
+                        }),
+                    )),
+            )
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("cancel"))
+                            .on_press(Message::Location(LocationMessage::Cancel)),
+                    )
+                    .push(save)
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    pub(super) fn update_location(
+        &mut self,
+        message: LocationMessage,
+    ) -> Task<cosmic::Action<Message>> {
+        match message {
+            LocationMessage::Select(index) => {
+                self.selected_location = Some(index);
+                self.location_form = None;
+                self.location_selection.clear();
+                self.location_target = None;
+                self.location_rename = self.locations[index].name.clone();
+                self.context_page = ContextPage::LocationDetail;
+                self.core.window.show_context = true;
+            }
+
+            LocationMessage::New => {
+                self.location_form = Some(LocationForm {
+                    name: String::new(),
+                    kind: LocationKind::ALL
+                        .iter()
+                        .position(|kind| *kind == LocationKind::default())
+                        .unwrap(),
+                    parent: 0,
+                });
+                self.context_page = ContextPage::LocationDetail;
+                self.core.window.show_context = true;
+            }
+
+            LocationMessage::Register(name) => {
+                self.locations.push(Location {
+                    name,
+                    ..Location::default()
+                });
+                self.save_locations();
+            }
+
+            LocationMessage::Name(name) => {
+                if let Some(form) = self.location_form.as_mut() {
+                    form.name = name;
+                }
+            }
+
+            LocationMessage::Kind(kind) => {
+                if let Some(form) = self.location_form.as_mut() {
+                    form.kind = kind;
+                }
+            }
+
+            LocationMessage::Parent(parent) => {
+                if let Some(form) = self.location_form.as_mut() {
+                    form.parent = parent;
+                }
+            }
+
+            LocationMessage::Save => {
+                let Some(form) = self.location_form.take() else {
+                    return Task::none();
+                };
+                let parent = form
+                    .parent
+                    .checked_sub(1)
+                    .map(|index| self.locations[index].name.clone())
+                    .unwrap_or_default();
+                self.locations.push(Location {
+                    name: form.name.trim().to_string(),
+                    kind: LocationKind::ALL[form.kind],
+                    parent,
+                });
+                self.save_locations();
+                return self.update_location(LocationMessage::Select(self.locations.len() - 1));
+            }
+
+            LocationMessage::Cancel => {
+                self.location_form = None;
+            }
+
+            LocationMessage::ToggleBook(index, ticked) => {
+                if ticked {
+                    self.location_selection.push(index);
+                } else {
+                    self.location_selection
+                        .retain(|selected| *selected != index);
+                }
+            }
+
+            LocationMessage::Target(target) => {
+                self.location_target = Some(target);
+            }
+
+            LocationMessage::MoveSelected => {
+                if let Some(target) = self.location_target {
+                    let name = self.locations[target].name.clone();
+                    let failed = self.record_edits(|app| {
+                        move_books(&mut app.books, &app.location_selection, &name)
+                    });
+                    self.location_selection.clear();
+                    return self.report_unsaved(failed);
+                }
+            }
+
+            LocationMessage::RenameInput(name) => {
+                self.location_rename = name;
+            }
+
+            LocationMessage::Rename => {
+                if let Some(index) = self.selected_location {
+                    let name = self.location_rename.trim().to_string();
+                    if let Err(err) = self.record_edits(|app| {
+                        rename_location(&mut app.locations, &mut app.books, index, &name)
+                    }) {
+                        eprintln!("Failed to rename location: {}", err);
+                        return self.toast(fl!("rename-failed", error = err.to_string()));
+                    }
+                    self.location_rename = name;
+                    self.save_locations();
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Registered locations in display order, each with its nesting depth:
+    /// top-level locations sorted by name, each followed by what it contains.
+    fn location_tree(&self) -> Vec<(usize, usize)> {
+        fn add(
+            locations: &[Location],
+            parent: Option<usize>,
+            depth: usize,
+            tree: &mut Vec<(usize, usize)>,
+        ) {
+            let mut children: Vec<usize> = (0..locations.len())
+                .filter(|index| {
+                    let location = &locations[*index];
+                    match parent {
+                        Some(parent) => locations[parent].matches(&location.parent),
+                        None => find_location(locations, &location.parent).is_none(),
+                    }
+                })
+                .collect();
+            children.sort_by_key(|index| locations[*index].name.to_lowercase());
+
+            for index in children {
+                // A location can't contain itself, even if the register says so.
+                if tree.iter().any(|(_, known)| *known == index) {
+                    continue;
+                }
+                tree.push((depth, index));
+                add(locations, Some(index), depth + 1, tree);
+            }
+        }
+
+        let mut tree = vec![];
+        add(&self.locations, None, 0, &mut tree);
+        tree
+    }
+
+    /// Indices into `books` of the books kept at `location`.
+    fn books_at(&self, location: &Location) -> Vec<usize> {
+        self.books
+            .iter()
+            .enumerate()
+            .filter(|(_, book)| {
+                book.data
+                    .as_ref()
+                    .is_some_and(|data| location.matches(&data.location))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Locations books refer to that are missing from the register, with the
+    /// number of books at each.
+    fn unregistered_locations(&self) -> BTreeMap<String, usize> {
+        let mut unregistered = BTreeMap::new();
+        for data in self.books.iter().filter_map(|book| book.data.as_ref()) {
+            let location = data.location.trim();
+            if !location.is_empty() && find_location(&self.locations, location).is_none() {
+                *unregistered.entry(location.to_string()).or_default() += 1;
+            }
+        }
+        unregistered
+    }
+
+    fn save_locations(&mut self) {
+        let data_path = Path::new(&self.config.data_path);
+        if let Err(err) = save_locations(data_path, &self.locations) {
+            eprintln!("Failed to save locations: {}", err);
+        }
+        self.refresh_location_options();
+    }
+
+    /// Rebuilds the labels of the location dropdowns, "no location" first.
+    pub(super) fn refresh_location_options(&mut self) {
+        self.location_options = std::iter::once(fl!("no-location"))
+            .chain(self.locations.iter().map(|location| location.name.clone()))
+            .collect();
+    }
+}
+
+pub(super) fn location_kind_label(kind: LocationKind) -> String {
+    match kind {
+        LocationKind::Room => fl!("location-room"),
+        LocationKind::Shelf => fl!("location-shelf"),
+        LocationKind::Box => fl!("location-box"),
+    }
+}
@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: MIT
+
+use super::{AppModel, Message, Page};
+use crate::book_data::format_price;
+use crate::fl;
+use crate::statistics::{Statistics, Tally};
+use crate::valuation::{Valuation, WriteDown, write_valuation};
+use chrono::{Local, NaiveDate};
+use cosmic::iced::Length;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget::{self, scrollable};
+use std::path::Path;
+
+/// Choices for the age from which unsold books count as slow movers.
+pub const SLOW_MOVER_YEARS: [u32; 5] = [1, 2, 3, 5, 10];
+
+/// Number of authors listed, those with the most books in stock first.
+const TOP_AUTHORS: usize = 20;
+
+/// What the statistics page shows, computed once rather than on every redraw.
+pub struct StockFigures {
+    date: NaiveDate,
+    statistics: Statistics,
+    valuation: Valuation,
+}
+
+impl AppModel {
+    /// Computes the figures of the statistics page while it is shown, unless
+    /// they are still those of today.
+    pub(super) fn refresh_statistics(&mut self) {
+        if !matches!(self.nav.active_data::<Page>(), Some(Page::Statistics)) {
+            return;
+        }
+        let today = Local::now().date_naive();
+        if self
+            .stock_figures
+            .as_ref()
+            .is_some_and(|figures| figures.date == today)
+        {
+            return;
+        }
+
+        let years = SLOW_MOVER_YEARS[self.slow_mover_years];
+        self.stock_figures = Some(StockFigures {
+            date: today,
+            statistics: Statistics::compute(
+                &self.books,
+                &self.sales,
+                &self.authorities,
+                today,
+                years,
+            ),
+            valuation: Valuation::compute(
+                &self.books,
+                &self.sales,
+                today,
+                self.valuation_write_downs(),
+            ),
+        });
+    }
+
+    pub(super) fn statistics_view(&self) -> Element<'_, Message> {
+        let Some(StockFigures {
+            statistics,
+            valuation,
+            ..
+        }) = &self.stock_figures
+        else {
+            return widget::text("").into();
+        };
+
+        let tally = |tally: &Tally| {
+            widget::text(format!(
+                "{} · {}",
+                fl!("book-count", count = tally.count),
+                format_price(tally.value)
+            ))
+        };
+
+        let stock = widget::settings::section()
+            .title(fl!("stock"))
+            .add(widget::settings::item(
+                fl!("stock-total"),
+                tally(&statistics.stock),
+            ))
+            .add(widget::settings::item(
+                fl!("average-time-in-stock"),
+                widget::text(
+                    statistics
+                        .average_days_in_stock
+                        .map_or_else(|| fl!("unknown"), |days| fl!("day-count", count = days)),
+                ),
+            ))
+            .add(widget::settings::item(
+                fl!("average-age"),
+                widget::text(
+                    statistics
+                        .average_age
+                        .map_or_else(|| fl!("unknown"), |years| fl!("year-count", count = years)),
+                ),
+            ));
+
+        let mut valuation_section = widget::settings::section()
+            .title(fl!("inventory-valuation"))
+            .add(widget::settings::item(
+                fl!("total-at-cost"),
+                widget::text(format_price(valuation.total_cost())),
+            ))
+            .add(widget::settings::item(
+                fl!("inventory-value"),
+                widget::text(format_price(valuation.total_value())),
+            ));
+        if valuation.without_cost() > 0 {
+            valuation_section = valuation_section.add(widget::text::caption(fl!(
+                "books-without-cost",
+                count = valuation.without_cost()
+            )));
+        }
+        let valuation_section = valuation_section.add(
+            widget::row()
+                .push(
+                    widget::checkbox(fl!("write-down-old-stock"), self.valuation_write_downs)
+                        .on_toggle(Message::ValuationWriteDowns),
+                )
+                .push(widget::horizontal_space())
+                .push(
+                    widget::button::standard(fl!("export-valuation"))
+                        .on_press(Message::ExportValuation),
+                )
+                .align_y(Vertical::Center),
+        );
+
+        let by_category = statistics.by_category.iter().fold(
+            widget::settings::section().title(fl!("by-category")),
+            |section, (category, stock)| {
+                section.add(widget::settings::item(category.to_string(), tally(stock)))
+            },
+        );
+
+        let by_condition = statistics.by_condition.iter().fold(
+            widget::settings::section().title(fl!("by-condition")),
+            |section, (condition, stock)| {
+                section.add(widget::settings::item(condition.to_string(), tally(stock)))
+            },
+        );
+
+        let by_language = statistics.by_language.iter().fold(
+            widget::settings::section().title(fl!("by-language")),
+            |section, (language, stock)| {
+                let language = match language.as_str() {
+                    "" => fl!("unknown"),
+                    language => language.to_string(),
+                };
+                section.add(widget::settings::item(language, tally(stock)))
+            },
+        );
+
+        let mut authors: Vec<_> = statistics.by_author.iter().collect();
+        authors.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(b.0)));
+        let by_author = authors.into_iter().take(TOP_AUTHORS).fold(
+            widget::settings::section().title(fl!("by-author")),
+            |section, (author, stock)| {
+                section.add(widget::settings::item(author.clone(), tally(stock)))
+            },
+        );
+
+        let sales_per_month = statistics.sales_per_month.iter().rev().fold(
+            widget::settings::section().title(fl!("sales-per-month")),
+            |section, ((year, month), sold)| {
+                section.add(widget::settings::item(
+                    format!("{year}-{month:02}"),
+                    tally(sold),
+                ))
+            },
+        );
+
+        let slow_movers = statistics.slow_movers.iter().fold(
+            widget::settings::section().title(fl!("slow-movers")),
+            |section, index| {
+                let book = &self.books[*index];
+                let data = book.data.as_ref().unwrap();
+                section.add(
+                    widget::mouse_area(widget::settings::item(
+                        format!("{:05} {}", book.id, data.title),
+                        widget::text(format_price(data.price)),
+                    ))
+                    .on_press(Message::SelectBook(*index)),
+                )
+            },
+        );
+
+        let slow_after = widget::row()
+            .push(widget::text(fl!("slow-movers-after")))
+            .push(widget::dropdown(
+                &self.slow_mover_options,
+                Some(self.slow_mover_years),
+                Message::SlowMoverYears,
+            ))
+            .align_y(Vertical::Center)
+            .spacing(Theme::default().cosmic().space_xxs());
+
+        scrollable(
+            widget::column()
+                .push(stock)
+                .push(valuation_section)
+                .push(sales_per_month)
+                .push(by_category)
+                .push(by_condition)
+                .push(by_language)
+                .push(by_author)
+                .push(slow_after)
+                .push(slow_movers)
+                .spacing(Theme::default().cosmic().space_m())
+                .width(Length::Fill),
+        )
+        .height(Length::Fill)
+        .into()
+    }
+
+    /// Configured write-downs if they are switched on for the valuation.
+    fn valuation_write_downs(&self) -> Option<&[WriteDown]> {
+        self.valuation_write_downs
+            .then_some(self.config.write_downs.as_slice())
+    }
+
+    /// Writes the inventory valuation as of today as CSV and PDF and opens the PDF.
+    pub(super) fn export_valuation(&self) {
+        let data_path = Path::new(&self.config.data_path);
+        let valuation = Valuation::compute(
+            &self.books,
+            &self.sales,
+            Local::now().date_naive(),
+            self.valuation_write_downs(),
+        );
+        let path = match write_valuation(data_path, &self.config.seller, &valuation) {
+            Ok(path) => path,
+            Err(err) => {
+                eprintln!("Failed to write inventory valuation: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = open::that_detached(&path) {
+            eprintln!("failed to open {path:?}: {err}");
+        }
+    }
+}
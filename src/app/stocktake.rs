@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: MIT
+
+use super::book_form::BookFormMessage;
+use super::{AppModel, ContextPage, Message, save_book_or_log};
+use crate::audit::{Audit, restock, save_audit};
+use crate::book::Book;
+use crate::fl;
+use crate::scan::normalize_isbn;
+use crate::status::Status;
+use cosmic::app::context_drawer;
+use cosmic::iced::alignment::Vertical;
+use cosmic::prelude::*;
+use cosmic::widget;
+use std::path::Path;
+
+/// Messages of the stocktaking drawer.
+#[derive(Debug, Clone)]
+pub enum AuditMessage {
+    /// Starts an audit of the selected location.
+    Start,
+    Tick(usize, bool),
+    Input(String),
+    Submit,
+    Finish,
+    Resume,
+    Withdraw(usize),
+    MoveHere(usize),
+    Restock(usize),
+    Close,
+}
+
+impl AppModel {
+    pub(super) fn audit_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Message>> {
+        let audit = self.audit.as_ref()?;
+        let content = if self.audit_finished {
+            self.audit_report(audit)
+        } else {
+            self.audit_checklist(audit)
+        };
+        Some(
+            context_drawer::context_drawer(content, Message::ToggleContextPage(ContextPage::Audit))
+                .title(fl!("stocktaking-at", location = audit.location.clone())),
+        )
+    }
+
+    fn audit_checklist<'a>(&'a self, audit: &'a Audit) -> Element<'a, Message> {
+        let space_xxs = Theme::default().cosmic().space_xxs();
+
+        let expected = self
+            .books
+            .iter()
+            .enumerate()
+            .filter(|(_, book)| audit.expects(book))
+            .fold(
+                widget::settings::section().title(fl!("expected-books")),
+                |section, (index, book)| {
+                    section.add(
+                        widget::checkbox(book_label(book), audit.found.contains(&book.id))
+                            .on_toggle(move |found| {
+                                Message::Audit(AuditMessage::Tick(index, found))
+                            }),
+                    )
+                },
+            );
+
+        let elsewhere = self
+            .books
+            .iter()
+            .filter(|book| audit.found.contains(&book.id) && !audit.expects(book))
+            .map(book_label)
+            .chain(audit.unknown.iter().cloned())
+            .fold(
+                widget::settings::section().title(fl!("found-unexpectedly")),
+                |section, label| section.add(widget::text(label)),
+            );
+
+        widget::column()
+            .push(widget::text(fl!("stocktaking-hint")))
+            .push(
+                widget::row()
+                    .push(
+                        widget::text_input(fl!("id-or-isbn"), &self.audit_input)
+                            .on_input(|input| Message::Audit(AuditMessage::Input(input)))
+                            .on_submit(|_| Message::Audit(AuditMessage::Submit)),
+                    )
+                    .push(
+                        widget::button::standard(fl!("add"))
+                            .on_press(Message::Audit(AuditMessage::Submit)),
+                    )
+                    .align_y(Vertical::Center)
+                    .spacing(space_xxs),
+            )
+            .push(expected)
+            .push(elsewhere)
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::destructive(fl!("cancel-stocktaking"))
+                            .on_press(Message::Audit(AuditMessage::Close)),
+                    )
+                    .push(
+                        widget::button::suggested(fl!("finish-stocktaking"))
+                            .on_press(Message::Audit(AuditMessage::Finish)),
+                    )
+                    .spacing(space_xxs),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    fn audit_report<'a>(&'a self, audit: &'a Audit) -> Element<'a, Message> {
+        let report = audit.report(&self.books);
+        let fix = |label, message| widget::button::text(label).on_press(Message::Audit(message));
+
+        let missing = report.missing.iter().fold(
+            widget::settings::section().title(fl!("missing-books")),
+            |section, index| {
+                section.add(widget::settings::item(
+                    book_label(&self.books[*index]),
+                    fix(fl!("withdraw"), AuditMessage::Withdraw(*index)),
+                ))
+            },
+        );
+
+        let misplaced = report.misplaced.iter().fold(
+            widget::settings::section().title(fl!("misplaced-books")),
+            |section, index| {
+                let book = &self.books[*index];
+                let recorded = book.data.as_ref().unwrap().location.clone();
+                section.add(widget::settings::item(
+                    format!("{} ({})", book_label(book), recorded),
+                    fix(fl!("move-here"), AuditMessage::MoveHere(*index)),
+                ))
+            },
+        );
+
+        let written_off = report.written_off.iter().fold(
+            widget::settings::section().title(fl!("written-off-books")),
+            |section, index| {
+                section.add(widget::settings::item(
+                    book_label(&self.books[*index]),
+                    fix(fl!("restock"), AuditMessage::Restock(*index)),
+                ))
+            },
+        );
+
+        let unknown = report.unknown.iter().fold(
+            widget::settings::section().title(fl!("unknown-codes")),
+            |section, code| {
+                let mut create = widget::button::text(fl!("create-book-with-isbn"));
+                if let Some(isbn) = normalize_isbn(code) {
+                    create = create.on_press(Message::BookForm(BookFormMessage::New(isbn)));
+                }
+                section.add(widget::settings::item(code.clone(), create))
+            },
+        );
+
+        widget::column()
+            .push(missing)
+            .push(misplaced)
+            .push(written_off)
+            .push(unknown)
+            .push(
+                widget::row()
+                    .push(
+                        widget::button::standard(fl!("continue-stocktaking"))
+                            .on_press(Message::Audit(AuditMessage::Resume)),
+                    )
+                    .push(
+                        widget::button::suggested(fl!("close-stocktaking"))
+                            .on_press(Message::Audit(AuditMessage::Close)),
+                    )
+                    .spacing(Theme::default().cosmic().space_xxs()),
+            )
+            .spacing(Theme::default().cosmic().space_s())
+            .into()
+    }
+
+    pub(super) fn update_audit(&mut self, message: AuditMessage) -> Task<cosmic::Action<Message>> {
+        match message {
+            AuditMessage::Start => {
+                let Some(location) = self.selected_location.map(|index| &self.locations[index])
+                else {
+                    return Task::none();
+                };
+                self.audit = Some(Audit::new(&location.name));
+                self.audit_finished = false;
+                self.save_audit();
+                self.context_page = ContextPage::Audit;
+                self.core.window.show_context = true;
+            }
+
+            AuditMessage::Tick(index, found) => {
+                if let Some(audit) = self.audit.as_mut() {
+                    audit.tick(self.books[index].id, found);
+                    self.save_audit();
+                }
+            }
+
+            AuditMessage::Input(input) => {
+                self.audit_input = input;
+            }
+
+            AuditMessage::Submit => {
+                let code = std::mem::take(&mut self.audit_input);
+                if !code.trim().is_empty() {
+                    self.record_audit_code(&code);
+                }
+            }
+
+            AuditMessage::Finish => {
+                self.audit_finished = true;
+            }
+
+            AuditMessage::Resume => {
+                self.audit_finished = false;
+            }
+
+            AuditMessage::Withdraw(index) => {
+                let book = &mut self.books[index];
+                if book
+                    .data
+                    .as_mut()
+                    .is_some_and(|data| data.set_status(Status::Withdrawn))
+                {
+                    save_book_or_log(book);
+                    return self.match_wants();
+                }
+            }
+
+            AuditMessage::MoveHere(index) => {
+                let Some(audit) = &self.audit else {
+                    return Task::none();
+                };
+                let book = &mut self.books[index];
+                if let Some(data) = book.data.as_mut() {
+                    data.location = audit.location.clone();
+                    save_book_or_log(book);
+                }
+            }
+
+            AuditMessage::Restock(index) => {
+                let book = &mut self.books[index];
+                if let Some(data) = book.data.as_mut() {
+                    restock(data);
+                    save_book_or_log(book);
+                    return self.match_wants();
+                }
+            }
+
+            AuditMessage::Close => {
+                self.audit = None;
+                self.audit_finished = false;
+                self.save_audit();
+                self.core.window.show_context = false;
+            }
+        }
+        Task::none()
+    }
+
+    /// Records a scanned or typed code in the running audit.
+    pub(super) fn record_audit_code(&mut self, code: &str) {
+        if let Some(audit) = self.audit.as_mut() {
+            audit.record(code, &self.books);
+            self.save_audit();
+        }
+    }
+
+    fn save_audit(&self) {
+        let data_path = Path::new(&self.config.data_path);
+        if let Err(err) = save_audit(data_path, self.audit.as_ref()) {
+            eprintln!("Failed to save audit: {}", err);
+        }
+    }
+}
+
+fn book_label(book: &Book) -> String {
+    let title = book
+        .data
+        .as_ref()
+        .map(|data| data.title.as_str())
+        .unwrap_or_default();
+    format!("{:05} {}", book.id, title)
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::book::Book;
+use crate::book_data::BookData;
+use crate::location::Location;
+use crate::scan::lookup;
+use crate::status::Status;
+
+/// A stocktake of one location. Stored while running, so it can span days.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Audit {
+    /// Name of the audited location.
+    pub location: String,
+    /// IDs of the books found at the location.
+    #[serde(default)]
+    pub found: Vec<u32>,
+    /// Scanned or entered codes that match no book.
+    #[serde(default)]
+    pub unknown: Vec<String>,
+}
+
+/// Outcome of an audit, as indices into `books`.
+#[derive(Debug, Default)]
+pub struct AuditReport {
+    /// Expected at the location but not found.
+    pub missing: Vec<usize>,
+    /// Found at the location but recorded elsewhere.
+    pub misplaced: Vec<usize>,
+    /// Found at the location but recorded as sold, withdrawn or out of stock.
+    pub written_off: Vec<usize>,
+    pub unknown: Vec<String>,
+}
+
+impl Audit {
+    pub fn new(location: &str) -> Self {
+        Audit {
+            location: location.to_string(),
+            ..Audit::default()
+        }
+    }
+
+    /// Records a scanned ID label or ISBN as found.
+    pub fn record(&mut self, code: &str, books: &[Book]) {
+        match lookup(code, books) {
+            Some(index) => self.tick(books[index].id, true),
+            None => {
+                let code = code.trim().to_string();
+                if !self.unknown.contains(&code) {
+                    self.unknown.push(code);
+                }
+            }
+        }
+    }
+
+    pub fn tick(&mut self, id: u32, found: bool) {
+        self.found.retain(|found_id| *found_id != id);
+        if found {
+            self.found.push(id);
+        }
+    }
+
+    /// Whether `book` is recorded at the audited location.
+    pub fn is_kept_here(&self, book: &Book) -> bool {
+        let location = Location {
+            name: self.location.clone(),
+            ..Location::default()
+        };
+        book.data
+            .as_ref()
+            .is_some_and(|data| location.matches(&data.location))
+    }
+
+    /// Whether `book` should be on the shelf: kept at the audited location, in
+    /// stock, and neither sold nor withdrawn.
+    pub fn expects(&self, book: &Book) -> bool {
+        self.is_kept_here(book)
+            && book.data.as_ref().is_some_and(|data| {
+                data.in_stock() && !matches!(data.status, Status::Sold | Status::Withdrawn)
+            })
+    }
+
+    pub fn report(&self, books: &[Book]) -> AuditReport {
+        let mut report = AuditReport {
+            unknown: self.unknown.clone(),
+            ..AuditReport::default()
+        };
+
+        for (index, book) in books.iter().enumerate() {
+            let found = self.found.contains(&book.id);
+            if !found {
+                if self.expects(book) {
+                    report.missing.push(index);
+                }
+            } else if !self.is_kept_here(book) {
+                report.misplaced.push(index);
+            } else if !self.expects(book) {
+                report.written_off.push(index);
+            }
+        }
+        report
+    }
+}
+
+/// Puts a book that turned up on the shelf back into stock.
+pub fn restock(data: &mut BookData) {
+    match data.status {
+        Status::Sold => data.return_copy(),
+        _ => {
+            if data.status == Status::Withdrawn {
+                data.set_status(Status::Available);
+            }
+            if !data.in_stock() {
+                data.quantity = 1;
+            }
+        }
+    }
+}
+
+fn audit_path(data_path: &Path) -> PathBuf {
+    data_path.join("audit.toml")
+}
+
+/// Loads the audit in progress, if any.
+pub fn load_audit(data_path: &Path) -> Option<Audit> {
+    match read_to_string(audit_path(data_path)) {
+        Ok(content) => match toml::from_str(&content) {
+            Ok(audit) => Some(audit),
+            Err(err) => {
+                eprintln!("Couldn't parse audit:\n{}", err);
+                None
+            }
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => {
+            eprintln!("Failed to read audit: {}", err);
+            None
+        }
+    }
+}
+
+/// Stores the audit in progress, or removes it when `audit` is `None`.
+pub fn save_audit(
+    data_path: &Path,
+    audit: Option<&Audit>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match audit {
+        Some(audit) => std::fs::write(audit_path(data_path), toml::to_string(audit)?)?,
+        None => match std::fs::remove_file(audit_path(data_path)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,282 @@
+//! The authority register: the preferred form of names of persons and
+//! publishers, with the variants found on title pages and in old records.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::book::{Book, save_book};
+use crate::book_data::{BookData, sort_name};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthorityKind {
+    #[default]
+    Person,
+    Publisher,
+}
+
+impl AuthorityKind {
+    pub const ALL: [AuthorityKind; 2] = [AuthorityKind::Person, AuthorityKind::Publisher];
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Authority {
+    /// Preferred form, for persons in sort order like "Mann, Thomas".
+    pub name: String,
+    #[serde(default)]
+    pub kind: AuthorityKind,
+    /// Other forms of the name that refer to the same person or publisher.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Identifier in the Gemeinsame Normdatei, empty if unknown.
+    #[serde(default)]
+    pub gnd: String,
+}
+
+impl Authority {
+    /// Whether `name` is the preferred form or one of the aliases, ignoring case,
+    /// punctuation and, for persons, the order of surname and given names.
+    pub fn matches(&self, name: &str) -> bool {
+        let key = key(name, self.kind);
+        !key.is_empty()
+            && std::iter::once(&self.name)
+                .chain(&self.aliases)
+                .any(|known| self::key(known, self.kind) == key)
+    }
+
+    /// Whether the preferred form or an alias contains `query`, ignoring case,
+    /// or `query` is a form of the name.
+    pub fn contains(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        std::iter::once(&self.name)
+            .chain(&self.aliases)
+            .any(|known| known.to_lowercase().contains(&query))
+            || self.matches(&query)
+    }
+
+    /// Whether `data` names this person or publisher in any form.
+    pub fn used_by(&self, data: &BookData) -> bool {
+        names(data, self.kind).iter().any(|name| self.matches(name))
+    }
+}
+
+/// Comparison form of a name: lower case, letters and digits only, and persons
+/// in sort order.
+fn key(name: &str, kind: AuthorityKind) -> String {
+    let name = match kind {
+        AuthorityKind::Person => sort_name(name),
+        AuthorityKind::Publisher => name.to_string(),
+    };
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct Authorities {
+    #[serde(default)]
+    authorities: Vec<Authority>,
+}
+
+fn authorities_path(data_path: &Path) -> PathBuf {
+    data_path.join("authorities.toml")
+}
+
+pub fn load_authorities(data_path: &Path) -> Vec<Authority> {
+    match read_to_string(authorities_path(data_path)) {
+        Ok(content) => match toml::from_str::<Authorities>(&content) {
+            Ok(authorities) => authorities.authorities,
+            Err(err) => {
+                eprintln!("Couldn't parse authorities:\n{}", err);
+                vec![]
+            }
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+        Err(err) => {
+            eprintln!("Failed to read authorities: {}", err);
+            vec![]
+        }
+    }
+}
+
+pub fn save_authorities(
+    data_path: &Path,
+    authorities: &[Authority],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let serialized = toml::to_string(&Authorities {
+        authorities: authorities.to_vec(),
+    })?;
+    std::fs::write(authorities_path(data_path), serialized)?;
+    Ok(())
+}
+
+/// Index into `authorities` of the entry of `kind` that `name` refers to.
+pub fn find_authority(authorities: &[Authority], kind: AuthorityKind, name: &str) -> Option<usize> {
+    authorities
+        .iter()
+        .position(|authority| authority.kind == kind && authority.matches(name))
+}
+
+/// The preferred form of `name` if it is registered, otherwise `name` itself.
+pub fn preferred_name(authorities: &[Authority], kind: AuthorityKind, name: &str) -> String {
+    find_authority(authorities, kind, name)
+        .map_or_else(|| name.to_string(), |index| authorities[index].name.clone())
+}
+
+/// Up to `limit` registered names and aliases of `kind` that contain `input`,
+/// for completing a form field. Nothing if `input` is already a preferred name.
+pub fn suggestions<'a>(
+    authorities: &'a [Authority],
+    kind: AuthorityKind,
+    input: &str,
+    limit: usize,
+) -> Vec<&'a str> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return vec![];
+    }
+
+    let mut suggestions = vec![];
+    for authority in authorities
+        .iter()
+        .filter(|authority| authority.kind == kind)
+    {
+        if authority.name.to_lowercase() == input {
+            return vec![];
+        }
+        if std::iter::once(&authority.name)
+            .chain(&authority.aliases)
+            .any(|name| name.to_lowercase().contains(&input))
+        {
+            suggestions.push(authority.name.as_str());
+        }
+    }
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// Names of `kind` used in `data`: the contributors or the publisher.
+pub fn names(data: &BookData, kind: AuthorityKind) -> Vec<String> {
+    match kind {
+        AuthorityKind::Person => data
+            .contributors()
+            .into_iter()
+            .map(|contributor| contributor.name)
+            .collect(),
+        AuthorityKind::Publisher if data.publisher.trim().is_empty() => vec![],
+        AuthorityKind::Publisher => vec![data.publisher.clone()],
+    }
+}
+
+/// Names of `kind` used by books that match no authority, with the number of
+/// books using each, most used first.
+pub fn unregistered_names(
+    books: &[Book],
+    authorities: &[Authority],
+    kind: AuthorityKind,
+) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for data in books.iter().filter_map(|book| book.data.as_ref()) {
+        for name in names(data, kind) {
+            if find_authority(authorities, kind, &name).is_none() {
+                *counts.entry(name).or_default() += 1;
+            }
+        }
+    }
+
+    let mut names: Vec<(String, usize)> = counts.into_iter().collect();
+    names.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    names
+}
+
+/// Number of books that use a name of the authority at `index`.
+pub fn book_count(books: &[Book], authorities: &[Authority], index: usize) -> usize {
+    books
+        .iter()
+        .filter_map(|book| book.data.as_ref())
+        .filter(|data| authorities[index].used_by(data))
+        .count()
+}
+
+/// Replaces every name in `books` that `authority` matches with its preferred
+/// form and saves the changed books. A book that can't be saved keeps its
+/// names. Returns the number of books changed and the number that could not be
+/// saved.
+pub fn apply_authority(books: &mut [Book], authority: &Authority) -> (usize, usize) {
+    let mut changed = 0;
+    let mut failed = 0;
+    for book in books.iter_mut() {
+        let Some(mut data) = book.data.clone() else {
+            continue;
+        };
+
+        let modified = match authority.kind {
+            AuthorityKind::Person => {
+                let mut contributors = data.contributors();
+                let mut modified = false;
+                for contributor in contributors.iter_mut() {
+                    if authority.matches(&contributor.name) && contributor.name != authority.name {
+                        contributor.name = authority.name.clone();
+                        modified = true;
+                    }
+                }
+                if modified {
+                    data.set_contributors(contributors);
+                }
+                modified
+            }
+            AuthorityKind::Publisher => {
+                if authority.matches(&data.publisher) && data.publisher != authority.name {
+                    data.publisher = authority.name.clone();
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+
+        if !modified {
+            continue;
+        }
+        let old = book.data.replace(data);
+        if let Err(err) = save_book(book) {
+            eprintln!("Failed to save book {:05}: {}", book.id, err);
+            book.data = old;
+            failed += 1;
+        } else {
+            changed += 1;
+        }
+    }
+    (changed, failed)
+}
+
+/// Adds `variants` as aliases of the authority at `index`, skipping those it
+/// already matches, and rewrites the books that use them. The aliases are only
+/// registered once all books were saved, so the variants of books that could
+/// not be saved are still listed as unregistered. Returns the number of books
+/// changed and the number that could not be saved.
+pub fn merge_into(
+    authorities: &mut [Authority],
+    books: &mut [Book],
+    index: usize,
+    variants: &[String],
+) -> (usize, usize) {
+    let mut authority = authorities[index].clone();
+    for variant in variants {
+        if !authority.matches(variant) {
+            authority.aliases.push(variant.trim().to_string());
+        }
+    }
+    let (changed, failed) = apply_authority(books, &authority);
+    if failed == 0 {
+        authorities[index] = authority;
+    }
+    (changed, failed)
+}
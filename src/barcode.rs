@@ -0,0 +1,67 @@
+//! Code 128 barcodes, set B, which covers all printable ASCII characters.
+
+/// Bar and space widths in modules for every Code 128 symbol, bar first.
+const PATTERNS: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232",
+];
+const START_B: usize = 104;
+const STOP: &str = "2331112";
+
+/// Encodes `text` as alternating bar and space widths in modules, starting with
+/// a bar. Returns `None` if `text` contains characters outside printable ASCII.
+pub fn code128(text: &str) -> Option<Vec<u8>> {
+    let mut values = vec![START_B];
+    for c in text.chars() {
+        if !(' '..='~').contains(&c) {
+            return None;
+        }
+        values.push(c as usize - 32);
+    }
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(position, value)| position.max(1) * value)
+        .sum::<usize>()
+        % 103;
+    values.push(checksum);
+
+    let widths = values
+        .iter()
+        .flat_map(|value| PATTERNS[*value].bytes())
+        .chain(STOP.bytes())
+        .map(|width| width - b'0')
+        .collect();
+    Some(widths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_checksum() {
+        // Start B 104, then P 48, J 42, J 42, 1 17, 2 18, 3 19 and C 35, each
+        // weighted by its position: 879 % 103 = 55.
+        let widths = code128("PJJ123C").unwrap();
+        let checksum: Vec<u8> = PATTERNS[55].bytes().map(|width| width - b'0').collect();
+        assert_eq!(&widths[8 * 6..9 * 6], checksum.as_slice());
+        assert_eq!(widths.len(), 9 * 6 + STOP.len());
+    }
+
+    #[test]
+    fn rejects_non_printable() {
+        assert_eq!(code128("Ä"), None);
+    }
+}
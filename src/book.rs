@@ -1,3 +1,4 @@
+use chrono::Local;
 use fs4::fs_std::FileExt;
 use regex::bytes::Regex;
 use std::fs::File;
@@ -5,17 +6,25 @@ use std::fs::OpenOptions;
 use std::fs::create_dir_all;
 use std::io;
 use std::io::Read;
-use std::path::Path;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use walkdir::WalkDir;
 
 use crate::book_data::BookData;
+use crate::changelog::{append_changes, logs_path};
+use crate::records::next_id;
+use crate::sale::load_sales;
 
 #[derive(Clone)]
 pub struct Book {
     pub id: u32,
     pub data: Option<BookData>,
     pub file: Arc<File>,
+    /// Where `file` is, inside the data directory.
+    pub path: PathBuf,
     pub description_hovered: bool,
 }
 
@@ -31,23 +40,7 @@ pub fn load_data(data_path: &Path) -> Vec<Book> {
         }
     }
 
-    let mut file_names: Vec<String> = vec![];
-    let files_iterator = WalkDir::new(data_path)
-        .max_depth(1)
-        .into_iter()
-        .filter_map(|entry| entry.ok())
-        .filter(|e| {
-            e.metadata().unwrap().is_file()
-                && e.path().extension().map_or(false, |ext| ext == "toml")
-        });
-
-    for entry in files_iterator {
-        file_names.push(entry.file_name().to_str().unwrap().to_string())
-    }
-
-    let filename_regex = Regex::new(r"^\d{5}.toml$").unwrap();
-
-    file_names.retain(|f| filename_regex.is_match(&f.as_bytes()));
+    let file_names = book_file_names(data_path);
 
     let mut file_list = vec![];
     for file_name in file_names {
@@ -98,6 +91,7 @@ pub fn load_data(data_path: &Path) -> Vec<Book> {
             id,
             data: Option::Some(deserialized),
             file: Arc::new(file),
+            path: data_path.join(&file_name),
             description_hovered: false,
         };
         books.push(book);
@@ -105,6 +99,47 @@ pub fn load_data(data_path: &Path) -> Vec<Book> {
     books
 }
 
+/// Names of the book files in `data_path`, like `00042.toml`.
+pub fn book_file_names(data_path: &Path) -> Vec<String> {
+    let mut file_names: Vec<String> = vec![];
+    let files_iterator = WalkDir::new(data_path)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|e| {
+            e.metadata().unwrap().is_file()
+                && e.path().extension().map_or(false, |ext| ext == "toml")
+        });
+
+    for entry in files_iterator {
+        file_names.push(entry.file_name().to_str().unwrap().to_string())
+    }
+
+    let filename_regex = Regex::new(r"^\d{5}.toml$").unwrap();
+
+    file_names.retain(|f| filename_regex.is_match(&f.as_bytes()));
+    file_names
+}
+
+/// Reads all books without locking their files, for looking at the data while
+/// the app holds the locks. Files that can't be read are reported and skipped.
+pub fn read_data(data_path: &Path) -> Vec<(u32, BookData)> {
+    let mut books = vec![];
+    for file_name in book_file_names(data_path) {
+        let path = data_path.join(&file_name);
+        let read_result = File::open(&path)
+            .map_err(|err| (Box::new(err) as Box<dyn std::error::Error>, String::new()))
+            .and_then(|mut file| read_book_from_file(&mut file));
+
+        match read_result {
+            Ok(data) => books.push((file_name[..5].parse().unwrap(), data)),
+            Err((err, _)) => eprintln!("Couldn't read {:#?}: {}", path, err),
+        }
+    }
+    books.sort_by_key(|(id, _)| *id);
+    books
+}
+
 pub fn read_book_from_file(
     file: &mut File,
 ) -> Result<BookData, (Box<dyn std::error::Error>, String)> {
@@ -123,3 +158,144 @@ pub fn read_book_from_file(
 
     Ok(deserialized.unwrap())
 }
+
+/// Replaces the content of the (already locked) book file with `data`.
+pub fn write_book_to_file(file: &File, data: &BookData) -> Result<(), Box<dyn std::error::Error>> {
+    let serialized = toml::to_string(data)?;
+
+    let mut file = file;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(serialized.as_bytes())?;
+    file.sync_all()?;
+
+    Ok(())
+}
+
+/// Writes the data of `book` to its file and logs the fields that changed.
+pub fn save_book(book: &Book) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(data) = &book.data else {
+        return Ok(());
+    };
+    let old = read_locked_book(&book.file).unwrap_or_default();
+    write_book_to_file(&book.file, data)?;
+    log_changes(book, &old, data);
+    Ok(())
+}
+
+/// What the (already locked) book file holds, `None` if it can't be read.
+fn read_locked_book(file: &File) -> Option<BookData> {
+    let mut file = file;
+    let mut file_content = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut file_content).ok()?;
+    toml::from_str(&file_content).ok()
+}
+
+/// Appends the fields that differ between `old` and `new` to the change log
+/// of `book`. A failure is reported but doesn't undo the change.
+fn log_changes(book: &Book, old: &BookData, new: &BookData) {
+    let Some(data_path) = book.path.parent() else {
+        return;
+    };
+    if let Err(err) = append_changes(data_path, book.id, new.changes_from(old)) {
+        eprintln!("Failed to log changes to book {:05}: {}", book.id, err);
+    }
+}
+
+/// Creates the file of a new book under the next free ID and keeps it locked.
+pub fn create_book(
+    data_path: &Path,
+    mut data: BookData,
+) -> Result<Book, Box<dyn std::error::Error>> {
+    create_dir_all(data_path)?;
+    data.added.get_or_insert_with(|| Local::now().date_naive());
+
+    let mut id = next_book_id(data_path);
+
+    loop {
+        if id > 99999 {
+            return Err("no free book ID left".into());
+        }
+
+        let file = match open_new_book_file(data_path, id) {
+            Ok(file) => file,
+            // Another instance took this ID in the meantime.
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                id += 1;
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        return lock_new_book(data_path, id, file, data);
+    }
+}
+
+/// One above the ID of every book there ever was: those on file, those with a
+/// change log, which outlives the book, and those in the sales ledger. IDs of
+/// deleted books are never given out again, so a new book can't inherit their
+/// history, sales or want matches.
+fn next_book_id(data_path: &Path) -> u32 {
+    let sold = load_sales(data_path)
+        .iter()
+        .flat_map(|sale| &sale.items)
+        .map(|item| item.book_id + 1)
+        .max()
+        .unwrap_or(1);
+    next_id(data_path)
+        .max(next_id(&logs_path(data_path)))
+        .max(sold)
+}
+
+/// Creates the file of a book under its former ID again, such as when its
+/// deletion is undone. Fails if the ID has been taken since.
+pub fn restore_book(
+    data_path: &Path,
+    id: u32,
+    data: BookData,
+) -> Result<Book, Box<dyn std::error::Error>> {
+    let file = open_new_book_file(data_path, id)?;
+    lock_new_book(data_path, id, file, data)
+}
+
+/// Removes the file of `book`. Its change log is kept and records every field
+/// as emptied.
+pub fn delete_book(data_path: &Path, book: &Book) -> io::Result<()> {
+    std::fs::remove_file(data_path.join(format!("{:05}.toml", book.id)))?;
+    if let Some(data) = &book.data {
+        log_changes(book, data, &BookData::default());
+    }
+    Ok(())
+}
+
+fn open_new_book_file(data_path: &Path, id: u32) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(data_path.join(format!("{id:05}.toml")))
+}
+
+/// Locks and fills the file of a new book. The log records every field as set
+/// from empty.
+fn lock_new_book(
+    data_path: &Path,
+    id: u32,
+    file: File,
+    data: BookData,
+) -> Result<Book, Box<dyn std::error::Error>> {
+    if !file.try_lock_exclusive()? {
+        return Err(format!("failed to acquire lock for new book {id:05}").into());
+    }
+    write_book_to_file(&file, &data)?;
+
+    let book = Book {
+        id,
+        data: Some(data),
+        file: Arc::new(file),
+        path: data_path.join(format!("{id:05}.toml")),
+        description_hovered: false,
+    };
+    log_changes(&book, &BookData::default(), book.data.as_ref().unwrap());
+    Ok(book)
+}
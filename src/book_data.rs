@@ -1,10 +1,18 @@
+use chrono::{Local, NaiveDate};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+use crate::imprint::ImprintDate;
+use crate::physical::{BookFormat, Pagination};
+use crate::status::{Status, StatusChange};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct BookData {
+    /// Authors for display and search, joined from `contributors` when those are
+    /// given. Records written before contributors existed only have this.
     pub author: String,
     pub title: String,
-    pub year: u16,
+    pub year: ImprintDate,
     pub cover: String,
     pub location: String,
     pub condition: u8,
@@ -14,10 +22,10 @@ pub struct BookData {
     pub description: String,
     pub language: String,
     pub isbn: String,
-    pub pages: String,
-    pub format: String,
+    pub pages: Pagination,
+    pub format: BookFormat,
     pub weight: u16,
-    pub price: u16,
+    pub price: u32,
     pub cover_url: String,
     pub keywords: Vec<String>,
     pub new: bool,
@@ -25,5 +33,424 @@ pub struct BookData {
     pub signed: bool,
     pub unused: bool,
     pub personal_notice: String,
+    /// Print-on-demand titles that never run out, regardless of `quantity`.
     pub unlimited: bool,
+    /// Copies on hand. Records written before this field existed hold one copy.
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+    /// Bought from a private seller, so sold under the margin scheme of § 25a UStG.
+    #[serde(default)]
+    pub differential_taxation: bool,
+    #[serde(default)]
+    pub status: Status,
+    /// Every status transition with its time, oldest first.
+    #[serde(default)]
+    pub status_history: Vec<StatusChange>,
+    /// Day the book was entered into stock. Unknown for records written before
+    /// this field existed.
+    #[serde(default)]
+    pub added: Option<NaiveDate>,
+    /// Cost of one copy in cents, for valuing the stock. Unknown if `None`.
+    #[serde(default)]
+    pub purchase_price: Option<u32>,
+    /// Copies that cost one cent more than `purchase_price`, the first ones
+    /// sold, so that the copies of a lot add up to the lot price.
+    #[serde(default)]
+    pub purchase_remainder: u32,
+    #[serde(default)]
+    pub purchase_date: Option<NaiveDate>,
+    /// Number of the lot in the `lots/` register the book was bought with.
+    #[serde(default)]
+    pub lot: Option<u32>,
+    /// Earlier owners of the copy, oldest first.
+    #[serde(default)]
+    pub previous_owners: Vec<String>,
+    /// Bookplate or owner's stamp, empty if there is none.
+    #[serde(default)]
+    pub ex_libris: String,
+    #[serde(default)]
+    pub binding: Option<Binding>,
+    /// Further details of the binding such as spine, gilding or edges.
+    #[serde(default)]
+    pub binding_details: String,
+    /// Plates, woodcuts, maps and the like, e.g. "mit 12 Holzschnitten".
+    #[serde(default)]
+    pub illustrations: String,
+    /// Collation formula or leaf count, including plates.
+    #[serde(default)]
+    pub collation: String,
+    /// Place of printing as given in the imprint.
+    #[serde(default)]
+    pub printing_place: String,
+    /// Entries in standard bibliographies, e.g. "Hain 1234" or "VD16 L 1234".
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Who signed or inscribed the copy, and the wording of a dedication.
+    #[serde(default)]
+    pub inscription: String,
+    /// Everyone who contributed to the book in the order of the title page.
+    #[serde(default)]
+    pub contributors: Vec<Contributor>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    #[default]
+    Author,
+    Editor,
+    Translator,
+    Illustrator,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Author,
+        Role::Editor,
+        Role::Translator,
+        Role::Illustrator,
+    ];
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Contributor {
+    /// Name in sort order, e.g. "Goethe, Johann Wolfgang von".
+    pub name: String,
+    #[serde(default)]
+    pub role: Role,
+}
+
+impl Contributor {
+    /// Name in reading order, e.g. "Johann Wolfgang von Goethe".
+    pub fn display_name(&self) -> String {
+        match self.name.split_once(',') {
+            Some((surname, given)) => format!("{} {}", given.trim(), surname.trim()),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Lower case name particles that German sort order puts after the given names.
+const PARTICLES: [&str; 8] = ["von", "van", "vom", "zu", "zur", "de", "der", "den"];
+
+/// Turns a name in reading order into sort order, so "Johann Wolfgang von Goethe"
+/// becomes "Goethe, Johann Wolfgang von". Names that already contain a comma or
+/// consist of a single word are kept.
+pub fn sort_name(name: &str) -> String {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.contains(',') {
+        return name;
+    }
+    let Some((given, surname)) = name.rsplit_once(' ') else {
+        return name;
+    };
+
+    let words: Vec<&str> = given.split(' ').collect();
+    let first_particle = words
+        .iter()
+        .rposition(|word| !PARTICLES.contains(word))
+        .map_or(0, |last_given| last_given + 1);
+    let (given, particles) = words.split_at(first_particle);
+    if given.is_empty() {
+        // "van Gogh" alone has no given names to move the particle behind.
+        return name;
+    }
+    let mut sorted = format!("{surname}, {}", given.join(" "));
+    if !particles.is_empty() {
+        sorted.push(' ');
+        sorted.push_str(&particles.join(" "));
+    }
+    sorted
+}
+
+/// Material of the binding.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Binding {
+    /// Ganzleder.
+    FullLeather,
+    /// Halbleder.
+    HalfLeather,
+    /// Pergament.
+    Vellum,
+    /// Leinen.
+    Cloth,
+    /// Halbleinen.
+    HalfCloth,
+    /// Pappband.
+    Boards,
+    /// Broschur.
+    Wrappers,
+    Other,
+}
+
+impl Binding {
+    pub const ALL: [Binding; 8] = [
+        Binding::FullLeather,
+        Binding::HalfLeather,
+        Binding::Vellum,
+        Binding::Cloth,
+        Binding::HalfCloth,
+        Binding::Boards,
+        Binding::Wrappers,
+        Binding::Other,
+    ];
+}
+
+fn default_quantity() -> u32 {
+    1
+}
+
+impl BookData {
+    pub fn in_stock(&self) -> bool {
+        self.unlimited || self.quantity > 0
+    }
+
+    /// The contributors, or for older records the author as the only one.
+    pub fn contributors(&self) -> Vec<Contributor> {
+        if !self.contributors.is_empty() || self.author.trim().is_empty() {
+            return self.contributors.clone();
+        }
+        vec![Contributor {
+            name: self.author.clone(),
+            role: Role::Author,
+        }]
+    }
+
+    /// Names of the contributors with `role`, joined by semicolons.
+    pub fn names(&self, role: Role) -> String {
+        self.contributors()
+            .iter()
+            .filter(|contributor| contributor.role == role)
+            .map(|contributor| contributor.name.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Replaces the contributors and derives `author` from them: the authors, or
+    /// everyone if the book has no author, such as an anthology.
+    pub fn set_contributors(&mut self, contributors: Vec<Contributor>) {
+        self.contributors = contributors;
+        self.author = self.names(Role::Author);
+        if self.author.is_empty() {
+            self.author = self
+                .contributors
+                .iter()
+                .map(|contributor| contributor.name.as_str())
+                .collect::<Vec<_>>()
+                .join("; ");
+        }
+    }
+
+    /// Whether any contributor's name contains `query`, ignoring case.
+    pub fn has_contributor(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        self.contributors().iter().any(|contributor| {
+            contributor.name.to_lowercase().contains(&query)
+                || contributor.display_name().to_lowercase().contains(&query)
+        })
+    }
+
+    /// Whether any of the provenance and bibliographic details of rare books is set.
+    pub fn has_rare_details(&self) -> bool {
+        !self.previous_owners.is_empty()
+            || !self.ex_libris.is_empty()
+            || self.binding.is_some()
+            || !self.binding_details.is_empty()
+            || !self.illustrations.is_empty()
+            || !self.collation.is_empty()
+            || !self.printing_place.is_empty()
+            || !self.references.is_empty()
+            || !self.inscription.is_empty()
+    }
+
+    /// Removes one copy from stock. Returns `false` if there was none left to sell.
+    pub fn take_copy(&mut self) -> bool {
+        if self.unlimited {
+            return true;
+        }
+        if self.quantity == 0 {
+            return false;
+        }
+        self.quantity -= 1;
+        true
+    }
+
+    /// Moves the book to `status` and records the transition. Returns `false` if
+    /// the transition is not allowed from the current status.
+    pub fn set_status(&mut self, status: Status) -> bool {
+        if !self.status.can_become(status) {
+            return false;
+        }
+
+        self.status = status;
+        self.status_history.push(StatusChange {
+            status,
+            at: Local::now(),
+        });
+        true
+    }
+
+    /// Sells one copy, marking the record sold once the last copy is gone.
+    pub fn sell_copy(&mut self) -> bool {
+        if !self.status.is_for_sale() || !self.take_copy() {
+            return false;
+        }
+
+        if !self.in_stock() {
+            self.set_status(Status::Sold);
+        } else if self.status == Status::Reserved {
+            self.set_status(Status::Available);
+        }
+        true
+    }
+
+    /// Puts a sold copy back into stock.
+    pub fn return_copy(&mut self) {
+        if !self.unlimited {
+            self.quantity += 1;
+        }
+        if self.status == Status::Sold {
+            self.set_status(Status::Returned);
+        }
+    }
+
+    /// Fields that differ from `old`, sorted by name. The status history is left
+    /// out, it records itself.
+    pub fn changes_from(&self, old: &BookData) -> Vec<FieldChange> {
+        let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+            (serde_json::to_value(old), serde_json::to_value(self))
+        else {
+            return vec![];
+        };
+
+        new.iter()
+            .filter(|(field, value)| {
+                field.as_str() != "status_history" && old.get(field.as_str()) != Some(value)
+            })
+            .map(|(field, value)| FieldChange {
+                field: field.clone(),
+                old: display_value(field, old.get(field.as_str()).unwrap_or(&Value::Null)),
+                new: display_value(field, value),
+            })
+            .collect()
+    }
+
+    /// `self` with the fields that differ between `from` and `to` set as in
+    /// `to`. Other fields keep their current values, so changes made to them
+    /// since are not lost.
+    pub fn with_changes(&self, from: &BookData, to: &BookData) -> serde_json::Result<BookData> {
+        let (Value::Object(mut data), Value::Object(from), Value::Object(to)) = (
+            serde_json::to_value(self)?,
+            serde_json::to_value(from)?,
+            serde_json::to_value(to)?,
+        ) else {
+            return Ok(self.clone());
+        };
+
+        for field in from.keys().chain(to.keys()) {
+            match to.get(field) {
+                Some(value) if from.get(field) != Some(value) => {
+                    data.insert(field.clone(), value.clone());
+                }
+                None if from.contains_key(field) => {
+                    data.remove(field);
+                }
+                _ => {}
+            }
+        }
+        serde_json::from_value(Value::Object(data))
+    }
+}
+
+/// A field of a book that was changed, with both values as text.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+fn display_value(field: &str, value: &Value) -> String {
+    match (field, value) {
+        (_, Value::Null) => String::new(),
+        (_, Value::String(text)) => text.clone(),
+        ("price" | "purchase_price", Value::Number(cents)) => cents
+            .as_u64()
+            .map_or_else(|| cents.to_string(), format_price),
+        (_, Value::Array(items)) if items.iter().all(Value::is_string) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(", "),
+        (_, value) => value.to_string(),
+    }
+}
+
+/// Formats an amount given in cents, e.g. `1250` as `12,50 €`.
+pub fn format_price(cents: impl Into<u64>) -> String {
+    let cents = cents.into();
+    format!("{},{:02} €", cents / 100, cents % 100)
+}
+
+/// Parses an amount like `12,50`, `12.5` or `12 €` into cents.
+pub fn parse_price(text: &str) -> Option<u32> {
+    let text = text.trim().trim_end_matches('€').trim();
+    let (euros, cents) = text.split_once([',', '.']).unwrap_or((text, ""));
+
+    let euros: u32 = euros.parse().ok()?;
+    let cents: u32 = match cents.len() {
+        0 => 0,
+        1 => cents.parse::<u32>().ok()? * 10,
+        2 => cents.parse().ok()?,
+        _ => return None,
+    };
+
+    euros.checked_mul(100)?.checked_add(cents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_names_with_particles() {
+        assert_eq!(
+            sort_name("Johann Wolfgang von Goethe"),
+            "Goethe, Johann Wolfgang von"
+        );
+        assert_eq!(
+            sort_name("Goethe, Johann Wolfgang von"),
+            "Goethe, Johann Wolfgang von"
+        );
+        assert_eq!(sort_name("van Gogh"), "van Gogh");
+    }
+
+    #[test]
+    fn parses_prices() {
+        assert_eq!(parse_price("12,5"), Some(1250));
+        assert_eq!(parse_price("12.50 €"), Some(1250));
+        assert_eq!(parse_price("12,505"), None);
+    }
+
+    #[test]
+    fn sets_only_changed_fields() {
+        let before = BookData {
+            price: 1000,
+            quantity: 2,
+            ..Default::default()
+        };
+        let after = BookData {
+            price: 1200,
+            ..before.clone()
+        };
+        // A copy was sold after the price was raised.
+        let current = BookData {
+            quantity: 1,
+            ..after.clone()
+        };
+        let undone = current.with_changes(&after, &before).unwrap();
+        assert_eq!(undone.price, 1000);
+        assert_eq!(undone.quantity, 1);
+    }
 }
@@ -0,0 +1,139 @@
+//! Editing many books at once, like repricing a shelf or moving a box.
+
+use crate::book::{Book, save_book};
+use crate::book_data::BookData;
+use crate::history::BookChange;
+use crate::status::Status;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceChange {
+    /// New price in cents.
+    Set(u32),
+    /// Raise or, if negative, lower the price by the percentage, rounded to
+    /// the cent.
+    Percent(i32),
+}
+
+impl PriceChange {
+    /// The new price, `None` if it would be too high to store.
+    pub fn apply(self, price: u32) -> Option<u32> {
+        match self {
+            PriceChange::Set(cents) => Some(cents),
+            PriceChange::Percent(percent) => {
+                let factor = (100 + i64::from(percent)).max(0);
+                let cents = (i64::from(price) * factor + 50) / 100;
+                u32::try_from(cents).ok()
+            }
+        }
+    }
+}
+
+/// Why a bulk edit leaves a book as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skipped {
+    /// The book can't change to the new status, such as a sold book becoming
+    /// reserved.
+    Status,
+    /// The new price would be too high to store.
+    Price,
+}
+
+/// Changes to make to every selected book. Fields left `None` or empty are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkEdit {
+    pub location: Option<String>,
+    pub category: Option<u16>,
+    pub price: Option<PriceChange>,
+    /// One of `BulkEdit::STATUSES`; selling and taking back copies also
+    /// change the quantity and the sales ledger, so they are done one by one.
+    pub status: Option<Status>,
+    pub add_keywords: Vec<String>,
+    pub remove_keywords: Vec<String>,
+}
+
+impl BulkEdit {
+    /// Statuses a bulk edit can set.
+    pub const STATUSES: [Status; 3] = [Status::Available, Status::Reserved, Status::Withdrawn];
+
+    pub fn is_empty(&self) -> bool {
+        *self == BulkEdit::default()
+    }
+
+    /// `data` with the edit applied, or why the edit doesn't apply to the book.
+    /// Such books are left as they are.
+    pub fn apply(&self, data: &BookData) -> Result<BookData, Skipped> {
+        let mut data = data.clone();
+        if self.status.is_some_and(|status| {
+            status != data.status
+                && (!BulkEdit::STATUSES.contains(&status) || !data.set_status(status))
+        }) {
+            return Err(Skipped::Status);
+        }
+        if let Some(location) = &self.location {
+            data.location = location.clone();
+        }
+        if let Some(category) = self.category {
+            data.category = category;
+        }
+        if let Some(change) = self.price {
+            data.price = change.apply(data.price).ok_or(Skipped::Price)?;
+        }
+        data.keywords
+            .retain(|keyword| !self.remove_keywords.contains(keyword));
+        for keyword in &self.add_keywords {
+            if !data.keywords.contains(keyword) {
+                data.keywords.push(keyword.clone());
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Applies `edit` to the books at `indices` and saves those that changed.
+/// Returns the changes, for undoing the edit, and the number of books that
+/// could not be saved.
+pub fn bulk_edit(
+    books: &mut [Book],
+    indices: &[usize],
+    edit: &BulkEdit,
+) -> (Vec<BookChange>, usize) {
+    let mut changes = vec![];
+    let mut failed = 0;
+    for index in indices {
+        let book = &mut books[*index];
+        let Some(data) = book.data.as_ref() else {
+            continue;
+        };
+        let Ok(edited) = edit.apply(data) else {
+            continue;
+        };
+        if edited.changes_from(data).is_empty() {
+            continue;
+        }
+
+        let before = book.data.replace(edited).unwrap();
+        changes.push(BookChange::edited(book, before));
+        if let Err(err) = save_book(book) {
+            eprintln!("Failed to save book {:05}: {}", book.id, err);
+            failed += 1;
+        }
+    }
+    (changes, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_selling_to_the_sale() {
+        let edit = BulkEdit {
+            status: Some(Status::Sold),
+            ..Default::default()
+        };
+        assert_eq!(
+            edit.apply(&BookData::default()).err(),
+            Some(Skipped::Status)
+        );
+    }
+}
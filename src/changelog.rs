@@ -0,0 +1,112 @@
+//! Append-only log of the changes to each book, `changes/NNNNN.toml` in the
+//! data directory: who changed which field when, from what to what.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{OpenOptions, create_dir_all, read_to_string};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::book_data::FieldChange;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub at: DateTime<Local>,
+    /// Login name of whoever made the change.
+    pub user: String,
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// The entries as stored. Each write appends a `[[change]]` table, so earlier
+/// entries are never rewritten.
+#[derive(Deserialize, Serialize, Default)]
+struct LogFile {
+    #[serde(default)]
+    change: Vec<LogEntry>,
+}
+
+/// Number of writes to the change logs by this process.
+static APPENDED: AtomicU64 = AtomicU64::new(0);
+
+/// Directory of the change logs. The log of a deleted book is kept.
+pub fn logs_path(data_path: &Path) -> PathBuf {
+    data_path.join("changes")
+}
+
+pub fn log_path(data_path: &Path, id: u32) -> PathBuf {
+    logs_path(data_path).join(format!("{id:05}.toml"))
+}
+
+/// Appends `changes` of book `id` to its log, stamped with the current time
+/// and user.
+pub fn append_changes(
+    data_path: &Path,
+    id: u32,
+    changes: Vec<FieldChange>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let at = Local::now();
+    let user = current_user();
+    let entries = LogFile {
+        change: changes
+            .into_iter()
+            .map(|change| LogEntry {
+                at,
+                user: user.clone(),
+                field: change.field,
+                old: change.old,
+                new: change.new,
+            })
+            .collect(),
+    };
+
+    let path = log_path(data_path, id);
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(toml::to_string(&entries)?.as_bytes())?;
+    file.sync_all()?;
+    APPENDED.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Number of writes to the change logs by this process so far. A log read
+/// earlier is out of date once this has grown.
+pub fn appended() -> u64 {
+    APPENDED.load(Ordering::Relaxed)
+}
+
+/// All logged changes of book `id`, oldest first. Empty if nothing was logged.
+pub fn load_changes(data_path: &Path, id: u32) -> Vec<LogEntry> {
+    let path = log_path(data_path, id);
+    let content = match read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return vec![],
+        Err(err) => {
+            eprintln!("Failed to read change log {:#?}: {}", path, err);
+            return vec![];
+        }
+    };
+
+    match toml::from_str::<LogFile>(&content) {
+        Ok(log) => log.change,
+        Err(err) => {
+            eprintln!("Couldn't parse change log {:#?}:\n{}", path, err);
+            vec![]
+        }
+    }
+}
+
+/// Login name of the user running the app, as set by the system.
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
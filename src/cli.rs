@@ -0,0 +1,669 @@
+//! Subcommands for scripts and cron jobs, run without opening a window.
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fs::{File, read_to_string, write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::acquisition::{LotReport, format_profit, load_lots};
+use crate::authority::{Authority, load_authorities};
+use crate::book::{Book, create_book, load_data, read_data, save_book};
+use crate::book_data::{BookData, Contributor, Role, format_price, parse_price, sort_name};
+use crate::config::Config;
+use crate::csv::csv_field;
+use crate::imprint::year_range;
+use crate::physical::page_range;
+use crate::sale::load_sales;
+use crate::status::Status;
+use crate::validate::fix;
+use crate::valuation::{Valuation, write_valuation};
+
+const USAGE: &str = "\
+Usage: antiquar [COMMAND]
+
+Without a command the app window opens.
+
+Commands:
+  list [--status STATUS] [--year FROM-TO] [--pages MIN-MAX] [--sort id|year] [--json]
+                                         List all books; circa dates match the years around them
+  show ID [--json]                       Show one book
+  search QUERY [--json]                  Find books by author, title, ISBN, keywords or description
+  export --format json|csv|toml [--output FILE]
+                                         Export all books
+  import FILE                            Add books from a JSON array; entries with an ID update
+                                         that book and fail if it doesn't exist
+  validate [--fix] [--json]              Check the data directory for problems; --fix repairs
+                                         the safe cases
+  set ID FIELD=VALUE...                  Change fields of a book. Contributors are separated by
+                                         semicolons, with an optional role such as
+                                         contributors='Goethe; illustrator:Kubin, Alfred'
+  lots [--json]                          Show the lots with their cost, revenue and profit
+  valuation [--date YYYY-MM-DD] [--write-downs]
+                                         Value the stock at purchase cost and write the report
+                                         as CSV and PDF; --write-downs values old stock at the
+                                         lower of cost and market value
+  help                                   Show this text";
+
+/// Names of the commands `run` knows.
+const COMMANDS: [&str; 12] = [
+    "list",
+    "show",
+    "search",
+    "export",
+    "import",
+    "validate",
+    "set",
+    "lots",
+    "valuation",
+    "help",
+    "--help",
+    "-h",
+];
+
+/// Whether `arg` names a command. Other arguments, like the files a desktop
+/// launcher passes, open the app window.
+pub fn is_command(arg: &str) -> bool {
+    COMMANDS.contains(&arg)
+}
+
+/// A book as printed by the commands: its ID followed by all fields.
+#[derive(Serialize)]
+struct Record<'a> {
+    id: u32,
+    #[serde(flatten)]
+    data: &'a BookData,
+}
+
+/// Runs the command in `args` (without the program name) and returns the exit code.
+pub fn run(config: &Config, args: &[String]) -> i32 {
+    let data_path = Path::new(&config.data_path);
+    let (command, args) = args.split_first().expect("no command given");
+    let result = match command.as_str() {
+        "list" => list(data_path, args),
+        "show" => show(data_path, args),
+        "search" => search(data_path, args),
+        "export" => export(data_path, args),
+        "import" => import(data_path, args),
+        "validate" => validate(data_path, args),
+        "set" => set(data_path, args),
+        "lots" => lots(data_path, args),
+        "valuation" => valuation(config, args),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            return 0;
+        }
+        _ => {
+            eprintln!("Unknown command {command:?}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            1
+        }
+    }
+}
+
+/// Value following `--name` in `args`.
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
+
+fn flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|arg| arg == name)
+}
+
+/// Arguments that are neither options nor their values.
+fn positional<'a>(args: &'a [String], options: &[&str]) -> Vec<&'a str> {
+    let mut positional = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if options.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with("--") {
+            positional.push(arg.as_str());
+        }
+    }
+    positional
+}
+
+fn parse_id(text: &str) -> Result<u32, Box<dyn Error>> {
+    text.parse()
+        .map_err(|_| format!("{text:?} is not a book ID").into())
+}
+
+/// Name of an enum variant as written to the book files, e.g. `half_leather`.
+fn variant_name(variant: impl Serialize) -> String {
+    serde_json::to_value(variant)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Prints `books` as JSON or as one line per book.
+fn print_books(books: &[(u32, BookData)], json: bool) -> Result<(), Box<dyn Error>> {
+    if json {
+        let records: Vec<Record> = books
+            .iter()
+            .map(|(id, data)| Record { id: *id, data })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(());
+    }
+
+    for (id, data) in books {
+        println!(
+            "{id:05}\t{}\t{}\t{}\t{}",
+            data.author,
+            data.title,
+            format_price(data.price),
+            variant_name(data.status)
+        );
+    }
+    Ok(())
+}
+
+fn list(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let status: Option<Status> = match option(args, "--status") {
+        Some(status) => Some(
+            serde_json::from_value(Value::String(status.to_string()))
+                .map_err(|_| format!("unknown status {status:?}"))?,
+        ),
+        None => None,
+    };
+
+    let years = match option(args, "--year") {
+        Some(years) => Some(year_range(years).ok_or(format!("invalid years {years:?}"))?),
+        None => None,
+    };
+    let pages = match option(args, "--pages") {
+        Some(pages) => Some(
+            page_range(pages).ok_or(format!("invalid page range {pages:?}, expected MIN-MAX"))?,
+        ),
+        None => None,
+    };
+
+    let mut books = read_data(data_path);
+    books.retain(|(_, data)| {
+        status.is_none_or(|status| data.status == status)
+            && years.is_none_or(|(from, to)| data.year.within(from, to))
+            && pages.is_none_or(|(min, max)| {
+                data.pages
+                    .pages
+                    .is_some_and(|pages| (min..=max).contains(&pages))
+            })
+    });
+    match option(args, "--sort") {
+        None | Some("id") => {}
+        Some("year") => books.sort_by_key(|(_, data)| data.year),
+        Some(key) => return Err(format!("unknown sort key {key:?}").into()),
+    }
+    print_books(&books, flag(args, "--json"))?;
+    Ok(0)
+}
+
+fn show(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let id = parse_id(positional(args, &[]).first().ok_or("missing book ID")?)?;
+    let books = read_data(data_path);
+    let (_, data) = books
+        .iter()
+        .find(|(book_id, _)| *book_id == id)
+        .ok_or(format!("no book with ID {id:05}"))?;
+
+    if flag(args, "--json") {
+        println!("{}", serde_json::to_string_pretty(&Record { id, data })?);
+    } else {
+        print!("{}", toml::to_string(data)?);
+    }
+    Ok(0)
+}
+
+fn search(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let query = positional(args, &[]).join(" ").to_lowercase();
+    if query.is_empty() {
+        return Err("missing search query".into());
+    }
+
+    // Registered names find books that use any form of them.
+    let authorities = load_authorities(data_path);
+    let authorities: Vec<&Authority> = authorities
+        .iter()
+        .filter(|authority| authority.contains(&query))
+        .collect();
+
+    let mut books = read_data(data_path);
+    books.retain(|(_, data)| {
+        [
+            &data.author,
+            &data.title,
+            &data.isbn,
+            &data.publisher,
+            &data.description,
+        ]
+        .into_iter()
+        .chain(&data.keywords)
+        .any(|field| field.to_lowercase().contains(&query))
+            || data.has_contributor(&query)
+            || authorities.iter().any(|authority| authority.used_by(data))
+    });
+    print_books(&books, flag(args, "--json"))?;
+    Ok(if books.is_empty() { 1 } else { 0 })
+}
+
+fn export(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let books = read_data(data_path);
+    let output = match option(args, "--format").unwrap_or("json") {
+        "json" => {
+            let records: Vec<Record> = books
+                .iter()
+                .map(|(id, data)| Record { id: *id, data })
+                .collect();
+            serde_json::to_string_pretty(&records)? + "\n"
+        }
+        "csv" => to_csv(&books),
+        "toml" => {
+            #[derive(Serialize)]
+            struct Export<'a> {
+                books: Vec<Record<'a>>,
+            }
+            toml::to_string(&Export {
+                books: books
+                    .iter()
+                    .map(|(id, data)| Record { id: *id, data })
+                    .collect(),
+            })?
+        }
+        format => return Err(format!("unknown export format {format:?}").into()),
+    };
+
+    match option(args, "--output") {
+        Some(path) => write(path, output)?,
+        None => print!("{output}"),
+    }
+    Ok(0)
+}
+
+/// One row per book with the columns marketplaces ask for. Prices are in euros
+/// with a decimal point; keywords, references and previous owners are separated
+/// by semicolons.
+fn to_csv(books: &[(u32, BookData)]) -> String {
+    let mut csv = String::from(
+        "id,author,editor,translator,illustrator,title,year,publisher,edition,isbn,language,pagination,pages,plates,format,height_cm,condition,\
+         price,quantity,status,location,keywords,description,binding,binding_details,\
+         illustrations,collation,printing_place,references,previous_owners,ex_libris,\
+         inscription\n",
+    );
+
+    for (id, data) in books {
+        let fields = [
+            format!("{id:05}"),
+            data.names(Role::Author),
+            data.names(Role::Editor),
+            data.names(Role::Translator),
+            data.names(Role::Illustrator),
+            data.title.clone(),
+            data.year.to_string(),
+            data.publisher.clone(),
+            data.edition.clone(),
+            data.isbn.clone(),
+            data.language.clone(),
+            data.pages.to_string(),
+            data.pages
+                .pages
+                .map(|pages| pages.to_string())
+                .unwrap_or_default(),
+            data.pages
+                .plates
+                .map(|plates| plates.to_string())
+                .unwrap_or_default(),
+            data.format.to_string(),
+            data.format
+                .height()
+                .map(|cm| cm.to_string())
+                .unwrap_or_default(),
+            data.condition.to_string(),
+            format!("{}.{:02}", data.price / 100, data.price % 100),
+            data.quantity.to_string(),
+            variant_name(data.status),
+            data.location.clone(),
+            data.keywords.join(";"),
+            data.description.clone(),
+            data.binding.map(variant_name).unwrap_or_default(),
+            data.binding_details.clone(),
+            data.illustrations.clone(),
+            data.collation.clone(),
+            data.printing_place.clone(),
+            data.references.join(";"),
+            data.previous_owners.join(";"),
+            data.ex_libris.clone(),
+            data.inscription.clone(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn import(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let path = positional(args, &[])
+        .first()
+        .copied()
+        .ok_or("missing file")?;
+    let entries: Vec<Value> = serde_json::from_str(&read_to_string(path)?)?;
+
+    // Every entry is checked before the first book is written, so a bad entry
+    // doesn't leave the import half done.
+    let mut books = load_data(data_path);
+    let mut updates: Vec<(usize, BookData)> = vec![];
+    let mut new_books = vec![];
+    for (position, mut entry) in entries.into_iter().enumerate() {
+        let number = position + 1;
+        let object = entry
+            .as_object_mut()
+            .ok_or(format!("entry {number} is not an object"))?;
+        let id = match object.remove("id") {
+            None => None,
+            Some(Value::Number(id)) => Some(
+                id.as_u64()
+                    .and_then(|id| u32::try_from(id).ok())
+                    .ok_or(format!("entry {number}: invalid id {id}"))?,
+            ),
+            Some(Value::String(id)) => {
+                Some(parse_id(&id).map_err(|err| format!("entry {number}: {err}"))?)
+            }
+            Some(id) => return Err(format!("entry {number}: invalid id {id}").into()),
+        };
+
+        match id {
+            Some(id) => {
+                // A book missing here may be locked by the app, so creating
+                // one would duplicate it.
+                let index = books.iter().position(|book| book.id == id).ok_or(format!(
+                    "entry {number}: no book with ID {id:05}, or it is open in the app"
+                ))?;
+                // Later entries for the same book build on the earlier ones.
+                let update = updates.iter_mut().find(|(updated, _)| *updated == index);
+                let data = match &update {
+                    Some((_, data)) => data,
+                    None => books[index].data.as_ref().unwrap(),
+                };
+                let data = merge(data, entry).map_err(|err| format!("entry {number}: {err}"))?;
+                match update {
+                    Some(update) => update.1 = data,
+                    None => updates.push((index, data)),
+                }
+            }
+            None => {
+                let new = BookData {
+                    quantity: 1,
+                    ..BookData::default()
+                };
+                new_books.push(merge(&new, entry).map_err(|err| format!("entry {number}: {err}"))?);
+            }
+        }
+    }
+
+    let updated = updates.len();
+    for (index, data) in updates {
+        let book = &mut books[index];
+        book.data = Some(data);
+        save_book(book)?;
+    }
+    let created = new_books.len();
+    for data in new_books {
+        let book = create_book(data_path, data)?;
+        println!("{:05}", book.id);
+    }
+
+    eprintln!("Created {created} and updated {updated} books");
+    Ok(0)
+}
+
+/// Overwrites the fields of `data` that `changes` contains.
+fn merge(data: &BookData, changes: Value) -> Result<BookData, Box<dyn Error>> {
+    let mut value = serde_json::to_value(data)?;
+    let mut changed = vec![];
+    if let (Some(object), Value::Object(changes)) = (value.as_object_mut(), changes) {
+        for (field, change) in changes {
+            changed.push(field.clone());
+            if !object.contains_key(&field) {
+                return Err(format!("unknown field {field:?}").into());
+            }
+            object.insert(field, change);
+        }
+    }
+    let mut merged: BookData = serde_json::from_value(value)?;
+    // `author` is derived from the contributors; setting it directly starts over
+    // with the given author as the only one.
+    if changed.iter().any(|field| field == "contributors") {
+        let contributors = std::mem::take(&mut merged.contributors);
+        merged.set_contributors(contributors);
+    } else if changed.iter().any(|field| field == "author") {
+        merged.contributors = vec![];
+    }
+    Ok(merged)
+}
+
+/// Parses contributors separated by semicolons, each optionally prefixed with
+/// its role, e.g. `Goethe, Johann Wolfgang von; illustrator:Alfred Kubin`.
+fn parse_contributors(text: &str) -> Result<Vec<Contributor>, Box<dyn Error>> {
+    let mut contributors = vec![];
+    for entry in text.split(';').filter(|entry| !entry.trim().is_empty()) {
+        let (role, name) = match entry.split_once(':') {
+            Some((role, name)) => {
+                let role = Role::ALL
+                    .into_iter()
+                    .find(|candidate| variant_name(candidate) == role.trim())
+                    .ok_or(format!("unknown role {:?}", role.trim()))?;
+                (role, name)
+            }
+            None => (Role::Author, entry),
+        };
+        contributors.push(Contributor {
+            name: sort_name(name),
+            role,
+        });
+    }
+    Ok(contributors)
+}
+
+fn validate(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    if flag(args, "--fix") {
+        let mut fixed = 0;
+        for book in load_data(data_path).iter_mut() {
+            if book.data.as_mut().is_some_and(fix) {
+                save_book(book)?;
+                fixed += 1;
+            }
+        }
+        eprintln!("Fixed {fixed} books");
+    }
+
+    let issues = crate::validate::validate(data_path);
+    if flag(args, "--json") {
+        println!("{}", serde_json::to_string_pretty(&issues)?);
+    } else {
+        for issue in &issues {
+            let fixable = if issue.fixable { " (fixable)" } else { "" };
+            println!("{}: {}{}", issue.file, issue.message, fixable);
+        }
+    }
+    Ok(if issues.is_empty() { 0 } else { 1 })
+}
+
+fn set(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let (id, assignments) = args.split_first().ok_or("missing book ID")?;
+    let id = parse_id(id)?;
+    if assignments.is_empty() {
+        return Err("nothing to set, expected FIELD=VALUE".into());
+    }
+
+    let mut books = load_data(data_path);
+    let book = books
+        .iter_mut()
+        .find(|book| book.id == id)
+        .ok_or(format!("no book with ID {id:05}, or it is open in the app"))?;
+    let data = book.data.as_ref().unwrap();
+    let current = serde_json::to_value(data)?;
+
+    let mut changes = serde_json::Map::new();
+    for assignment in assignments {
+        let (field, text) = assignment
+            .split_once('=')
+            .ok_or(format!("expected FIELD=VALUE, got {assignment:?}"))?;
+        let value = match (field, current.get(field)) {
+            (_, None) => return Err(format!("unknown field {field:?}").into()),
+            ("contributors", _) => serde_json::to_value(parse_contributors(text)?)?,
+            // Imprint dates other than exact years are text.
+            ("year", _) => Value::String(text.to_string()),
+            ("price" | "purchase_price", _) => {
+                Value::from(parse_price(text).ok_or(format!("invalid price {text:?}"))?)
+            }
+            (_, Some(Value::String(_))) => Value::String(text.to_string()),
+            (_, Some(Value::Bool(_))) => Value::Bool(text.parse()?),
+            (_, Some(Value::Number(_))) => Value::Number(text.parse::<u64>()?.into()),
+            (_, Some(Value::Array(_))) => Value::Array(
+                text.split(',')
+                    .map(|item| Value::String(item.trim().to_string()))
+                    .filter(|item| item.as_str() != Some(""))
+                    .collect(),
+            ),
+            // Unset optional fields, such as dates, which are strings in JSON.
+            (_, Some(Value::Null)) => {
+                serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+            }
+            (_, Some(_)) => serde_json::from_str(text)?,
+        };
+        changes.insert(field.to_string(), value);
+    }
+
+    let mut data = merge(data, Value::Object(changes))?;
+    // Status changes go through the transition rules and the history.
+    let status = data.status;
+    data.status = book.data.as_ref().unwrap().status;
+    if status != data.status && !data.set_status(status) {
+        return Err(format!(
+            "a {} book can't become {}",
+            variant_name(data.status),
+            variant_name(status)
+        )
+        .into());
+    }
+
+    book.data = Some(data);
+    save_book(book)?;
+    Ok(0)
+}
+
+/// Books opened read-only without locking, so that reports can be made while
+/// the app is open.
+fn read_books(data_path: &Path) -> Vec<Book> {
+    read_data(data_path)
+        .into_iter()
+        .filter_map(|(id, data)| {
+            let path = data_path.join(format!("{id:05}.toml"));
+            let file = File::open(&path).ok()?;
+            Some(Book {
+                id,
+                data: Some(data),
+                file: Arc::new(file),
+                path,
+                description_hovered: false,
+            })
+        })
+        .collect()
+}
+
+fn valuation(config: &Config, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    let date = match option(args, "--date") {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("{date:?} is not a date like 2025-12-31"))?,
+        None => Local::now().date_naive(),
+    };
+    let write_downs = flag(args, "--write-downs").then_some(config.write_downs.as_slice());
+
+    let data_path = Path::new(&config.data_path);
+    let books = read_books(data_path);
+    let sales = load_sales(data_path);
+    let valuation = Valuation::compute(&books, &sales, date, write_downs);
+    let path = write_valuation(data_path, &config.seller, &valuation)?;
+
+    println!("{}", path.with_extension("csv").display());
+    println!("{}", path.display());
+    println!("Value at cost: {}", format_price(valuation.total_cost()));
+    println!("Inventory value: {}", format_price(valuation.total_value()));
+    if valuation.without_cost() > 0 {
+        eprintln!(
+            "{} books have no purchase price and are valued at zero",
+            valuation.without_cost()
+        );
+    }
+    Ok(0)
+}
+
+fn lots(data_path: &Path, args: &[String]) -> Result<i32, Box<dyn Error>> {
+    /// A lot with its figures, amounts in cents.
+    #[derive(Serialize)]
+    struct LotRecord<'a> {
+        id: u32,
+        #[serde(flatten)]
+        lot: &'a crate::acquisition::Lot,
+        books: u32,
+        copies_sold: u32,
+        revenue: u64,
+        cost_of_sold: u64,
+        remaining_value: u64,
+        remaining_cost: u64,
+        profit: i64,
+    }
+
+    let books = read_books(data_path);
+    let sales = load_sales(data_path);
+    let lots = load_lots(data_path);
+    let records: Vec<LotRecord> = lots
+        .iter()
+        .map(|lot| {
+            let report = LotReport::compute(lot, &books, &sales);
+            LotRecord {
+                id: lot.id,
+                lot,
+                books: report.books,
+                copies_sold: report.copies_sold,
+                revenue: report.revenue,
+                cost_of_sold: report.cost_of_sold,
+                remaining_value: report.remaining_value,
+                remaining_cost: report.remaining_cost,
+                profit: report.profit(lot),
+            }
+        })
+        .collect();
+
+    if flag(args, "--json") {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(0);
+    }
+    for record in &records {
+        println!(
+            "{:05}\t{}\t{}\t{}\t{} books, {} sold\t{}\t{}",
+            record.id,
+            record.lot.date,
+            record.lot.source,
+            format_price(record.lot.price),
+            record.books,
+            record.copies_sold,
+            format_price(record.revenue),
+            format_profit(record.profit)
+        );
+    }
+    Ok(0)
+}
@@ -6,6 +6,10 @@ use std::io::Read;
 use std::path::Path;
 
 use crate::book_data::BookData;
+use crate::invoice::Seller;
+use crate::labels::LabelSheet;
+use crate::postage::Postage;
+use crate::valuation::{WriteDown, default_write_downs};
 
 use cosmic::cosmic_config::{self, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry};
 use serde::{Deserialize, Serialize};
@@ -14,6 +18,14 @@ use serde::{Deserialize, Serialize};
 #[version = 1]
 pub struct Config {
     pub data_path: String,
+    /// Letterhead, bank and tax details for invoices.
+    pub seller: Seller,
+    /// Carrier rate tables for postage suggestions.
+    pub postage: Postage,
+    /// Sheet layout used for printing shelf labels.
+    pub label_sheet: LabelSheet,
+    /// Age tiers for lower-of-cost-or-market write-downs in the inventory valuation.
+    pub write_downs: Vec<WriteDown>,
 }
 
 impl Default for Config {
@@ -27,10 +39,27 @@ impl Default for Config {
         let datapath = format!("{}/antiquar", datapath.unwrap());
         Config {
             data_path: datapath,
+            seller: Seller::default(),
+            postage: Postage::default(),
+            label_sheet: LabelSheet::default(),
+            write_downs: default_write_downs(),
         }
     }
 }
 
+impl Config {
+    /// Reads the configuration the app stores with cosmic-config, for use
+    /// without a running app. Falls back to the defaults.
+    pub fn load(app_id: &str) -> Config {
+        cosmic_config::Config::new(app_id, Config::VERSION)
+            .map(|context| match Config::get_entry(&context) {
+                Ok(config) => config,
+                Err((_errors, config)) => config,
+            })
+            .unwrap_or_default()
+    }
+}
+
 //pub fn load_config() -> Config {
 //    let args = env::args().collect::<Vec<String>>(); // Get command line arguments
 //
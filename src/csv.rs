@@ -0,0 +1,11 @@
+//! Comma-separated values for spreadsheets, as in the book export and the
+//! valuation report.
+
+/// Quotes `field` if it contains a separator, quote or line break.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
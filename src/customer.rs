@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::book::Book;
+use crate::book_data::BookData;
+use crate::records::{create_record, load_records, write_record};
+
+/// Something a customer is looking for. Empty fields match anything.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Want {
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Highest price in cents the customer is willing to pay.
+    #[serde(default)]
+    pub max_price: Option<u32>,
+}
+
+impl Want {
+    /// A want without author, title or keywords would match the whole stock.
+    pub fn is_empty(&self) -> bool {
+        self.author.trim().is_empty()
+            && self.title.trim().is_empty()
+            && self
+                .keywords
+                .iter()
+                .all(|keyword| keyword.trim().is_empty())
+    }
+
+    /// Whether `data` is a book for sale that fulfils this want.
+    pub fn matches(&self, data: &BookData) -> bool {
+        if self.is_empty() || !data.status.is_for_sale() || !data.in_stock() {
+            return false;
+        }
+
+        let contains = |haystack: &str, needle: &str| {
+            haystack
+                .to_lowercase()
+                .contains(&needle.trim().to_lowercase())
+        };
+
+        (self.author.trim().is_empty() || data.has_contributor(&self.author))
+            && contains(&data.title, &self.title)
+            && self.keywords.iter().all(|keyword| {
+                data.keywords.iter().any(|k| contains(k, keyword))
+                    || contains(&data.title, keyword)
+                    || contains(&data.description, keyword)
+            })
+            && self
+                .max_price
+                .is_none_or(|max_price| data.price <= max_price)
+    }
+}
+
+/// A book that fulfils one of the wants of a customer.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct WantMatch {
+    pub customer_id: u32,
+    pub book_id: u32,
+    /// Set once the customer has been told about the book.
+    #[serde(default)]
+    pub dismissed: bool,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct WantMatches {
+    #[serde(default)]
+    matches: Vec<WantMatch>,
+}
+
+/// A customer as stored in the `customers/` register.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Customer {
+    /// Taken from the file name, not stored in the file.
+    #[serde(skip)]
+    pub id: u32,
+    pub name: String,
+    /// Postal addresses, lines separated by newlines.
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub want_list: Vec<Want>,
+}
+
+pub fn customers_path(data_path: &Path) -> PathBuf {
+    data_path.join("customers")
+}
+
+pub fn load_customers(data_path: &Path) -> Vec<Customer> {
+    load_records(&customers_path(data_path))
+        .into_iter()
+        .map(|(id, customer)| Customer { id, ..customer })
+        .collect()
+}
+
+/// Writes `customer` to the register. New customers (with `id` 0) get the next
+/// free number, which is stored in `customer.id`.
+pub fn save_customer(
+    data_path: &Path,
+    customer: &mut Customer,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if customer.id == 0 {
+        customer.id = create_record(&customers_path(data_path), customer)?;
+        Ok(())
+    } else {
+        write_record(&customers_path(data_path), customer.id, customer)
+    }
+}
+
+/// All pairs of customer and book where the book fulfils one of the customer's wants.
+pub fn find_want_matches(customers: &[Customer], books: &[Book]) -> Vec<WantMatch> {
+    let mut matches = vec![];
+    for customer in customers {
+        for book in books {
+            let Some(data) = &book.data else {
+                continue;
+            };
+            if customer.want_list.iter().any(|want| want.matches(data)) {
+                matches.push(WantMatch {
+                    customer_id: customer.id,
+                    book_id: book.id,
+                    dismissed: false,
+                });
+            }
+        }
+    }
+    matches
+}
+
+fn want_matches_path(data_path: &Path) -> PathBuf {
+    customers_path(data_path).join("want_matches.toml")
+}
+
+/// Loads the matches found so far, so that only new ones are announced.
+pub fn load_want_matches(data_path: &Path) -> Vec<WantMatch> {
+    match read_to_string(want_matches_path(data_path)) {
+        Ok(content) => match toml::from_str::<WantMatches>(&content) {
+            Ok(want_matches) => want_matches.matches,
+            Err(err) => {
+                eprintln!("Couldn't parse want-list matches:\n{}", err);
+                vec![]
+            }
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+        Err(err) => {
+            eprintln!("Failed to read want-list matches: {}", err);
+            vec![]
+        }
+    }
+}
+
+pub fn save_want_matches(
+    data_path: &Path,
+    matches: &[WantMatch],
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(customers_path(data_path))?;
+    let serialized = toml::to_string(&WantMatches {
+        matches: matches.to_vec(),
+    })?;
+    std::fs::write(want_matches_path(data_path), serialized)?;
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! Undo and redo of changes to books, kept for the rest of the session.
+
+use std::path::Path;
+
+use crate::book::{Book, delete_book, restore_book, save_book};
+use crate::book_data::BookData;
+
+/// Number of steps that can be undone; older ones are forgotten.
+const LIMIT: usize = 100;
+
+/// A book before and after a change. `before` is `None` for a created book,
+/// `after` for a deleted one. Undoing or redoing an edit only sets the fields
+/// the edit changed.
+#[derive(Debug, Clone)]
+pub struct BookChange {
+    pub id: u32,
+    pub before: Option<BookData>,
+    pub after: Option<BookData>,
+}
+
+impl BookChange {
+    /// `book` was changed from `before` to what it holds now.
+    pub fn edited(book: &Book, before: BookData) -> Self {
+        BookChange {
+            id: book.id,
+            before: Some(before),
+            after: book.data.clone(),
+        }
+    }
+
+    pub fn created(book: &Book) -> Self {
+        BookChange {
+            id: book.id,
+            before: None,
+            after: book.data.clone(),
+        }
+    }
+
+    pub fn deleted(book: &Book) -> Self {
+        BookChange {
+            id: book.id,
+            before: book.data.clone(),
+            after: None,
+        }
+    }
+}
+
+/// Steps that can be undone and redone. A step is one action of the user and
+/// changes one book or, for bulk edits, many.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Vec<BookChange>>,
+    redo: Vec<Vec<BookChange>>,
+}
+
+impl History {
+    /// Adds a step to undo. A new step can't be followed by the ones undone
+    /// before it, so they can no longer be redone.
+    pub fn record(&mut self, changes: Vec<BookChange>) {
+        if changes.is_empty() {
+            return;
+        }
+        self.undo.push(changes);
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Puts the fields changed by the last step back as they were before it,
+    /// keeping later changes to other fields. Returns the number of books that
+    /// could not be restored, `None` if there was nothing to undo.
+    pub fn undo(&mut self, books: &mut Vec<Book>, data_path: &Path) -> Option<usize> {
+        let changes = self.undo.pop()?;
+        let mut failed = 0;
+        for change in changes.iter().rev() {
+            if !set_book(
+                books,
+                data_path,
+                change.id,
+                change.after.as_ref(),
+                change.before.as_ref(),
+            ) {
+                failed += 1;
+            }
+        }
+        self.redo.push(changes);
+        Some(failed)
+    }
+
+    /// Makes the last undone step again. Returns the number of books that could
+    /// not be changed, `None` if there was nothing to redo.
+    pub fn redo(&mut self, books: &mut Vec<Book>, data_path: &Path) -> Option<usize> {
+        let changes = self.redo.pop()?;
+        let mut failed = 0;
+        for change in &changes {
+            if !set_book(
+                books,
+                data_path,
+                change.id,
+                change.before.as_ref(),
+                change.after.as_ref(),
+            ) {
+                failed += 1;
+            }
+        }
+        self.undo.push(changes);
+        Some(failed)
+    }
+}
+
+/// Changes book `id` from `from` to `to`, creating or deleting its file as
+/// needed. An existing book only gets the fields in which `from` and `to`
+/// differ. Returns `false` if that failed.
+fn set_book(
+    books: &mut Vec<Book>,
+    data_path: &Path,
+    id: u32,
+    from: Option<&BookData>,
+    to: Option<&BookData>,
+) -> bool {
+    let position = books.iter().position(|book| book.id == id);
+    let result = match (position, to) {
+        (Some(position), Some(data)) => {
+            let book = &mut books[position];
+            match (book.data.as_ref(), from) {
+                (Some(current), Some(from)) => current.with_changes(from, data).map_err(Into::into),
+                _ => Ok(data.clone()),
+            }
+            .and_then(|data| {
+                book.data = Some(data);
+                save_book(book)
+            })
+        }
+        (Some(position), None) => {
+            let book = books.remove(position);
+            delete_book(data_path, &book).map_err(Into::into)
+        }
+        (None, Some(data)) => restore_book(data_path, id, data.clone()).map(|book| {
+            let at = books
+                .iter()
+                .position(|other| other.id > id)
+                .unwrap_or(books.len());
+            books.insert(at, book);
+        }),
+        (None, None) => Ok(()),
+    };
+
+    if let Err(err) = &result {
+        eprintln!("Failed to restore book {id:05}: {err}");
+    }
+    result.is_ok()
+}
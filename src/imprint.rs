@@ -0,0 +1,254 @@
+//! Imprint dates as the trade writes them: "1923", "um 1850", "[1923]",
+//! "1890–1895" and "o.J." for undated books.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Years around a circa date that a filter by year still accepts.
+const CIRCA_YEARS: u16 = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ImprintDate {
+    /// No date in the book and none established, written "o.J.".
+    #[default]
+    Unknown,
+    Exact(u16),
+    /// Approximate, written "um 1850".
+    Circa(u16),
+    /// Not printed in the book but established from other sources, written in
+    /// brackets like "[1923]".
+    Inferred(u16),
+    /// Published over several years, first and last, like "1890–1895".
+    Range(u16, u16),
+}
+
+impl ImprintDate {
+    /// Reads the forms written by `Display` and the common variants: "ca. 1850",
+    /// "1890-95", "n.d." and the like. `None` if `text` is no imprint date.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let compact: String = text
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '.')
+            .flat_map(char::to_lowercase)
+            .collect();
+        if matches!(compact.as_str(), "" | "0" | "oj" | "ohnejahr" | "nd" | "sd") {
+            return Some(ImprintDate::Unknown);
+        }
+
+        if let Some(inner) = text
+            .strip_prefix('[')
+            .and_then(|text| text.strip_suffix(']'))
+        {
+            return match ImprintDate::parse(inner)? {
+                ImprintDate::Exact(year) => Some(ImprintDate::Inferred(year)),
+                date => Some(date),
+            };
+        }
+
+        let lower = text.to_lowercase();
+        for prefix in ["um ", "ca. ", "ca.", "ca ", "circa ", "c. "] {
+            if let Some(year) = lower.strip_prefix(prefix) {
+                return parse_year(year).map(ImprintDate::Circa);
+            }
+        }
+
+        if let Some((first, last)) = text.split_once(['-', '–', '—', '/']) {
+            let (first, last) = (first.trim(), last.trim());
+            let first_year = parse_year(first)?;
+            // "1890-95" leaves out the century of the last year.
+            let last_year = if !last.is_empty() && last.len() < first.len() {
+                parse_year(&format!("{}{last}", &first[..first.len() - last.len()]))?
+            } else {
+                parse_year(last)?
+            };
+            return match first_year.cmp(&last_year) {
+                Ordering::Less => Some(ImprintDate::Range(first_year, last_year)),
+                Ordering::Equal => Some(ImprintDate::Exact(first_year)),
+                Ordering::Greater => None,
+            };
+        }
+
+        parse_year(text).map(ImprintDate::Exact)
+    }
+
+    /// First year the book may have been published in, `None` if undated.
+    pub fn earliest(&self) -> Option<u16> {
+        match *self {
+            ImprintDate::Unknown => None,
+            ImprintDate::Exact(year) | ImprintDate::Circa(year) | ImprintDate::Inferred(year) => {
+                Some(year)
+            }
+            ImprintDate::Range(first, _) => Some(first),
+        }
+    }
+
+    /// Last year the book may have been published in, `None` if undated.
+    pub fn latest(&self) -> Option<u16> {
+        match *self {
+            ImprintDate::Range(_, last) => Some(last),
+            date => date.earliest(),
+        }
+    }
+
+    /// A single year standing for the date, such as the middle of a range, for
+    /// figures like the average age of the stock.
+    pub fn year(&self) -> Option<u16> {
+        Some((self.earliest()? + self.latest()?) / 2)
+    }
+
+    /// Whether the book may have been published between `from` and `to`,
+    /// both included. Circa dates count for the years around them, too.
+    pub fn within(&self, from: u16, to: u16) -> bool {
+        let (Some(earliest), Some(latest)) = (self.earliest(), self.latest()) else {
+            return false;
+        };
+        let (earliest, latest) = match self {
+            ImprintDate::Circa(_) => (
+                earliest.saturating_sub(CIRCA_YEARS),
+                latest.saturating_add(CIRCA_YEARS),
+            ),
+            _ => (earliest, latest),
+        };
+        earliest <= to && latest >= from
+    }
+
+    /// Order of dates with the same years: exact before inferred before circa.
+    fn certainty(&self) -> u8 {
+        match self {
+            ImprintDate::Exact(_) => 0,
+            ImprintDate::Inferred(_) => 1,
+            ImprintDate::Circa(_) => 2,
+            ImprintDate::Range(..) => 3,
+            ImprintDate::Unknown => 4,
+        }
+    }
+}
+
+/// First and last year of a filter like "1850-1900" or "1923", `None` if
+/// `text` names no years.
+pub fn year_range(text: &str) -> Option<(u16, u16)> {
+    let date = ImprintDate::parse(text)?;
+    Some((date.earliest()?, date.latest()?))
+}
+
+/// A year of up to four digits, not zero.
+fn parse_year(text: &str) -> Option<u16> {
+    let text = text.trim();
+    if text.is_empty() || text.len() > 4 || !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok().filter(|year| *year > 0)
+}
+
+/// Chronological, undated books last.
+impl Ord for ImprintDate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |date: &ImprintDate| {
+            (
+                date.earliest().is_none(),
+                date.earliest(),
+                date.latest(),
+                date.certainty(),
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for ImprintDate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for ImprintDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImprintDate::Unknown => write!(f, "o.J."),
+            ImprintDate::Exact(year) => write!(f, "{year}"),
+            ImprintDate::Circa(year) => write!(f, "um {year}"),
+            ImprintDate::Inferred(year) => write!(f, "[{year}]"),
+            ImprintDate::Range(first, last) => write!(f, "{first}–{last}"),
+        }
+    }
+}
+
+/// Exact years are written as numbers, as in records from before other dates
+/// were possible, everything else as text.
+impl Serialize for ImprintDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ImprintDate::Exact(year) => serializer.serialize_u16(*year),
+            date => serializer.collect_str(date),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ImprintDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ImprintDateVisitor;
+
+        impl Visitor<'_> for ImprintDateVisitor {
+            type Value = ImprintDate;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a year or an imprint date like \"um 1850\"")
+            }
+
+            // Records written before other dates were possible hold 0 if undated.
+            fn visit_u64<E: de::Error>(self, year: u64) -> Result<ImprintDate, E> {
+                match u16::try_from(year) {
+                    Ok(0) => Ok(ImprintDate::Unknown),
+                    Ok(year) => Ok(ImprintDate::Exact(year)),
+                    Err(_) => Err(E::invalid_value(de::Unexpected::Unsigned(year), &self)),
+                }
+            }
+
+            fn visit_i64<E: de::Error>(self, year: i64) -> Result<ImprintDate, E> {
+                u64::try_from(year)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(year), &self))
+                    .and_then(|year| self.visit_u64(year))
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<ImprintDate, E> {
+                ImprintDate::parse(text)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(text), &self))
+            }
+        }
+
+        deserializer.deserialize_any(ImprintDateVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_trade_forms() {
+        assert_eq!(ImprintDate::parse("o.J."), Some(ImprintDate::Unknown));
+        assert_eq!(
+            ImprintDate::parse("um 1850"),
+            Some(ImprintDate::Circa(1850))
+        );
+        assert_eq!(
+            ImprintDate::parse("[1923]"),
+            Some(ImprintDate::Inferred(1923))
+        );
+        assert_eq!(
+            ImprintDate::parse("1890-95"),
+            Some(ImprintDate::Range(1890, 1895))
+        );
+    }
+
+    #[test]
+    fn writes_trade_forms() {
+        assert_eq!(ImprintDate::Unknown.to_string(), "o.J.");
+        assert_eq!(ImprintDate::Circa(1850).to_string(), "um 1850");
+        assert_eq!(ImprintDate::Inferred(1923).to_string(), "[1923]");
+        assert_eq!(ImprintDate::Range(1890, 1895).to_string(), "1890–1895");
+    }
+}
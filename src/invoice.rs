@@ -0,0 +1,430 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use crate::book::Book;
+use crate::book_data::format_price;
+use crate::customer::Customer;
+use crate::fl;
+use crate::labels::shorten;
+use crate::pdf::{Document, Font, Page, mm, text_width};
+use crate::sale::Sale;
+
+/// Seller details and tax settings printed on invoices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seller {
+    /// Name and address, one line each.
+    pub letterhead: Vec<String>,
+    /// Tax number or VAT identification number.
+    pub tax_number: String,
+    pub bank_details: Vec<String>,
+    /// Days the buyer has to pay the invoice.
+    pub payment_days: u16,
+    /// VAT rate for books in basis points, 700 being the German reduced rate of 7 %.
+    pub book_vat_rate: u16,
+    /// Small business under § 19 UStG that charges no VAT.
+    pub small_business: bool,
+}
+
+impl Default for Seller {
+    fn default() -> Self {
+        Seller {
+            letterhead: vec![],
+            tax_number: String::new(),
+            bank_details: vec![],
+            payment_days: 14,
+            book_vat_rate: 700,
+            small_business: false,
+        }
+    }
+}
+
+/// VAT contained in the gross amount `gross` at `rate` basis points.
+pub fn included_vat(gross: u32, rate: u16) -> u32 {
+    let gross = u64::from(gross);
+    let divisor = 10_000 + u64::from(rate);
+    let net = (gross * 10_000 + divisor / 2) / divisor;
+    (gross - net) as u32
+}
+
+pub fn invoices_path(data_path: &Path) -> PathBuf {
+    data_path.join("invoices")
+}
+
+/// Invoice numbers follow the gapless sale numbers.
+pub fn invoice_number(sale: &Sale) -> String {
+    format!("{:05}", sale.id)
+}
+
+pub fn invoice_path(data_path: &Path, sale: &Sale) -> PathBuf {
+    invoices_path(data_path).join(format!("invoice-{}.pdf", invoice_number(sale)))
+}
+
+pub fn delivery_note_path(data_path: &Path, sale: &Sale) -> PathBuf {
+    invoices_path(data_path).join(format!("delivery-note-{}.pdf", invoice_number(sale)))
+}
+
+struct Line {
+    book_id: u32,
+    description: String,
+    price: u32,
+    differential: bool,
+}
+
+/// Writes the invoice and the delivery note for `sale`.
+pub fn write_invoice(
+    data_path: &Path,
+    seller: &Seller,
+    sale: &Sale,
+    books: &[Book],
+    customers: &[Customer],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let lines: Vec<Line> = sale
+        .items
+        .iter()
+        .map(|item| {
+            let data = books
+                .iter()
+                .find(|book| book.id == item.book_id)
+                .and_then(|book| book.data.as_ref());
+            Line {
+                book_id: item.book_id,
+                description: data.map_or_else(String::new, |data| {
+                    format!("{}: {} ({})", data.author, data.title, data.year)
+                }),
+                price: item.price,
+                differential: data.is_some_and(|data| data.differential_taxation),
+            }
+        })
+        .collect();
+
+    let customer = sale
+        .customer_id
+        .and_then(|id| customers.iter().find(|customer| customer.id == id));
+    let mut recipient =
+        vec![customer.map_or_else(|| sale.customer.clone(), |customer| customer.name.clone())];
+    if let Some(address) = customer.and_then(|customer| customer.addresses.first()) {
+        recipient.extend(address.lines().map(str::to_string));
+    }
+
+    create_dir_all(invoices_path(data_path))?;
+
+    let invoice = invoice_document(seller, sale, &recipient, &lines);
+    write(invoice_path(data_path, sale), invoice.to_bytes())?;
+
+    let delivery_note = delivery_note_document(seller, sale, &recipient, &lines);
+    write(
+        delivery_note_path(data_path, sale),
+        delivery_note.to_bytes(),
+    )?;
+
+    Ok(())
+}
+
+// Layout in millimetres from the bottom left corner of the page.
+const LEFT: f32 = 25.0;
+const RIGHT: f32 = 190.0;
+const TOP: f32 = 277.0;
+const BOTTOM: f32 = 20.0;
+/// Space kept free for the amounts right of the descriptions.
+const AMOUNT_WIDTH: f32 = 25.0;
+const ID_COLUMN: f32 = LEFT + 12.0;
+const DESCRIPTION_COLUMN: f32 = LEFT + 28.0;
+const SIZE: f32 = 10.0;
+const LINE: f32 = 5.0;
+
+fn text(page: &mut Page, x: f32, y: f32, font: Font, text: &str) {
+    page.text(mm(x), mm(y), SIZE, font, text);
+}
+
+fn text_right(page: &mut Page, x: f32, y: f32, font: Font, text: &str) {
+    page.text_right(mm(x), mm(y), SIZE, font, text);
+}
+
+/// Draws letterhead, recipient and document heading, returning the height at
+/// which the body starts.
+fn heading(
+    page: &mut Page,
+    seller: &Seller,
+    title: &str,
+    sale: &Sale,
+    recipient: &[String],
+) -> f32 {
+    let mut y = TOP;
+    for (index, line) in seller.letterhead.iter().enumerate() {
+        let font = if index == 0 {
+            Font::Bold
+        } else {
+            Font::Regular
+        };
+        text_right(page, RIGHT, y, font, line);
+        y -= LINE;
+    }
+
+    let mut y = 230.0;
+    for line in recipient {
+        text(page, LEFT, y, Font::Regular, line);
+        y -= LINE;
+    }
+
+    let mut y = 190.0;
+    page.text(mm(LEFT), mm(y), 16.0, Font::Bold, title);
+    y -= 2.0 * LINE;
+
+    let date = sale.date.format("%d.%m.%Y").to_string();
+    for (label, value) in [
+        (fl!("invoice-number"), invoice_number(sale)),
+        (fl!("invoice-date"), date.clone()),
+        (fl!("delivery-date"), date),
+    ] {
+        text(page, LEFT, y, Font::Regular, &label);
+        text(page, LEFT + 40.0, y, Font::Regular, &value);
+        y -= LINE;
+    }
+
+    y - LINE
+}
+
+/// Puts `page` into `document` and starts a new one, returning the height at
+/// which it starts.
+fn next_page(document: &mut Document, page: &mut Page) -> f32 {
+    document.add_page(std::mem::replace(page, Page::a4()));
+    TOP
+}
+
+/// Draws the column headings of the table of books and returns the height
+/// below them.
+fn table_heading(page: &mut Page, mut y: f32, prices: bool) -> f32 {
+    text(page, LEFT, y, Font::Bold, &fl!("position"));
+    text(page, ID_COLUMN, y, Font::Bold, &fl!("book-id"));
+    text(page, DESCRIPTION_COLUMN, y, Font::Bold, &fl!("description"));
+    if prices {
+        text_right(page, RIGHT, y, Font::Bold, &fl!("amount"));
+    }
+    y -= 2.0;
+    page.hline(mm(LEFT), mm(RIGHT), mm(y));
+    y - LINE
+}
+
+/// Draws the table of books, with prices if `prices` is set, continuing on
+/// new pages as needed, and returns the height below it.
+fn table(
+    document: &mut Document,
+    page: &mut Page,
+    y: f32,
+    lines: &[Line],
+    prices: bool,
+    mark_differential: bool,
+) -> f32 {
+    let mut y = table_heading(page, y, prices);
+    let width = match prices {
+        true => mm(RIGHT - AMOUNT_WIDTH - DESCRIPTION_COLUMN),
+        false => mm(RIGHT - DESCRIPTION_COLUMN),
+    };
+
+    for (index, line) in lines.iter().enumerate() {
+        if y < BOTTOM {
+            y = next_page(document, page);
+            y = table_heading(page, y, prices);
+        }
+
+        let marker = match mark_differential && line.differential {
+            true => " *",
+            false => "",
+        };
+        let description =
+            shorten(&line.description, width - text_width(marker, SIZE), SIZE) + marker;
+
+        text(page, LEFT, y, Font::Regular, &(index + 1).to_string());
+        text(
+            page,
+            ID_COLUMN,
+            y,
+            Font::Regular,
+            &format!("{:05}", line.book_id),
+        );
+        text(page, DESCRIPTION_COLUMN, y, Font::Regular, &description);
+        if prices {
+            text_right(page, RIGHT, y, Font::Regular, &format_price(line.price));
+        }
+        y -= LINE;
+    }
+
+    y
+}
+
+fn invoice_document(
+    seller: &Seller,
+    sale: &Sale,
+    recipient: &[String],
+    lines: &[Line],
+) -> Document {
+    let mut document = Document::new();
+    let mut page = Page::a4();
+    let y = heading(&mut page, seller, &fl!("invoice"), sale, recipient);
+    let mut y = table(
+        &mut document,
+        &mut page,
+        y,
+        lines,
+        true,
+        !seller.small_business,
+    );
+
+    // Shipping and the total stay together.
+    if y < BOTTOM + 3.0 * LINE {
+        y = next_page(&mut document, &mut page);
+    }
+    if sale.shipping > 0 {
+        text(
+            &mut page,
+            DESCRIPTION_COLUMN,
+            y,
+            Font::Regular,
+            &fl!("shipping"),
+        );
+        text_right(
+            &mut page,
+            RIGHT,
+            y,
+            Font::Regular,
+            &format_price(sale.shipping),
+        );
+        y -= LINE;
+    }
+
+    y += LINE - 2.0;
+    page.hline(mm(LEFT), mm(RIGHT), mm(y));
+    y -= LINE;
+    text(&mut page, DESCRIPTION_COLUMN, y, Font::Bold, &fl!("total"));
+    let total = format_price(sale.revenue() + sale.shipping);
+    text_right(&mut page, RIGHT, y, Font::Bold, &total);
+    y -= 2.0 * LINE;
+
+    for note in invoice_notes(seller, sale, lines) {
+        if y < BOTTOM {
+            y = next_page(&mut document, &mut page);
+        }
+        text(&mut page, LEFT, y, Font::Regular, &note);
+        y -= LINE;
+    }
+
+    document.add_page(page);
+    document
+}
+
+/// Tax notes, payment terms and bank details printed below the total.
+fn invoice_notes(seller: &Seller, sale: &Sale, lines: &[Line]) -> Vec<String> {
+    let mut notes = vec![];
+    if seller.small_business {
+        notes.push(fl!("small-business-note"));
+    } else {
+        // Shipping is an ancillary service and shares the tax treatment of the
+        // books; it only falls under the margin scheme if all books do.
+        let regular: u32 = lines
+            .iter()
+            .filter(|line| !line.differential)
+            .map(|line| line.price)
+            .sum();
+        let taxable = if lines.iter().all(|line| line.differential) {
+            0
+        } else {
+            regular + sale.shipping
+        };
+
+        if taxable > 0 {
+            let vat = included_vat(taxable, seller.book_vat_rate);
+            notes.push(fl!(
+                "vat-included",
+                rate = format_rate(seller.book_vat_rate),
+                net = format_price(taxable - vat),
+                vat = format_price(vat)
+            ));
+        }
+        if lines.iter().any(|line| line.differential) {
+            notes.push(format!("* {}", fl!("margin-scheme-note")));
+        }
+    }
+
+    notes.push(fl!("payment-terms", days = seller.payment_days.to_string()));
+    notes.extend(seller.bank_details.iter().cloned());
+    if !seller.tax_number.is_empty() {
+        notes.push(format!("{} {}", fl!("tax-number"), seller.tax_number));
+    }
+    notes
+}
+
+fn delivery_note_document(
+    seller: &Seller,
+    sale: &Sale,
+    recipient: &[String],
+    lines: &[Line],
+) -> Document {
+    let mut document = Document::new();
+    let mut page = Page::a4();
+    let y = heading(&mut page, seller, &fl!("delivery-note"), sale, recipient);
+    table(&mut document, &mut page, y, lines, false, false);
+    document.add_page(page);
+    document
+}
+
+/// Formats a rate in basis points, e.g. `700` as `7` and `1950` as `19,5`.
+fn format_rate(rate: u16) -> String {
+    let fraction = format!("{:02}", rate % 100);
+    match fraction.trim_end_matches('0') {
+        "" => (rate / 100).to_string(),
+        fraction => format!("{},{}", rate / 100, fraction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_reduced_vat() {
+        assert_eq!(included_vat(1070, 700), 70);
+        // 10,00 € are 9,35 € net, rounded to the cent.
+        assert_eq!(included_vat(1000, 700), 65);
+        assert_eq!(included_vat(0, 700), 0);
+    }
+
+    #[test]
+    fn prints_the_margin_scheme_without_vat() {
+        let data_path = std::env::temp_dir().join(format!("invoice-test-{}", std::process::id()));
+        let book = crate::book::create_book(
+            &data_path,
+            crate::book_data::BookData {
+                author: "Goethe".to_string(),
+                title: "Faust".to_string(),
+                price: 1250,
+                differential_taxation: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let sale = Sale {
+            id: 1,
+            date: chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(),
+            channel: Default::default(),
+            items: vec![crate::sale::SaleItem {
+                book_id: book.id,
+                price: 1250,
+                returned: None,
+            }],
+            shipping: 190,
+            customer: String::new(),
+            customer_id: None,
+        };
+        write_invoice(&data_path, &Seller::default(), &sale, &[book], &[]).unwrap();
+        let pdf = std::fs::read(invoice_path(&data_path, &sale)).unwrap();
+        std::fs::remove_dir_all(&data_path).unwrap();
+
+        let pdf = String::from_utf8_lossy(&pdf);
+        // The book is marked as sold under the margin scheme.
+        assert!(pdf.contains("(Goethe: Faust \\(o.J.\\) *)"));
+        assert!(pdf.contains("25a UStG"));
+        // Shipping follows the books into the margin scheme, so no VAT is shown.
+        assert!(!pdf.contains("VAT at"));
+    }
+}
@@ -0,0 +1,128 @@
+//! The keywords used across all books, and renaming and merging them.
+
+use std::collections::BTreeMap;
+
+use crate::book::{Book, save_book};
+
+/// Every keyword with the number of books using it, sorted by keyword
+/// ignoring case.
+pub fn keyword_counts(books: &[Book]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for data in books.iter().filter_map(|book| book.data.as_ref()) {
+        for keyword in &data.keywords {
+            *counts.entry(keyword.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(keyword, count)| (keyword.to_string(), count))
+        .collect();
+    counts.sort_by_cached_key(|(keyword, _)| (keyword.to_lowercase(), keyword.clone()));
+    counts
+}
+
+/// Other keywords in `counts` that differ from `keyword` only in case or
+/// spacing, the usual result of keyword drift.
+pub fn variants<'a>(counts: &'a [(String, usize)], keyword: &str) -> Vec<&'a str> {
+    let key = normalize(keyword);
+    counts
+        .iter()
+        .map(|(other, _)| other.as_str())
+        .filter(|other| *other != keyword && normalize(other) == key)
+        .collect()
+}
+
+fn normalize(keyword: &str) -> String {
+    keyword
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Replaces `keywords` with `target` in every book and saves the changed books.
+/// A book that has several of them or `target` already keeps `target` once, at
+/// the first of their places. Renaming is merging a single keyword. A book that
+/// can't be saved keeps its keywords. Returns the number of books changed and
+/// the number that could not be saved.
+pub fn merge_keywords(books: &mut [Book], keywords: &[String], target: &str) -> (usize, usize) {
+    let target = target.trim();
+    let mut changed = 0;
+    let mut failed = 0;
+    for book in books.iter_mut() {
+        let Some(data) = book.data.as_mut() else {
+            continue;
+        };
+        if !data
+            .keywords
+            .iter()
+            .any(|keyword| keywords.contains(keyword))
+        {
+            continue;
+        }
+
+        let mut merged: Vec<String> = vec![];
+        for keyword in &data.keywords {
+            let keyword = match keywords.contains(keyword) {
+                true => target,
+                false => keyword.as_str(),
+            };
+            if !merged.iter().any(|known| known == keyword) {
+                merged.push(keyword.to_string());
+            }
+        }
+        if merged == data.keywords {
+            continue;
+        }
+
+        let old = std::mem::replace(&mut data.keywords, merged);
+        if let Err(err) = save_book(book) {
+            eprintln!("Failed to save book {:05}: {}", book.id, err);
+            book.data.as_mut().unwrap().keywords = old;
+            failed += 1;
+        } else {
+            changed += 1;
+        }
+    }
+    (changed, failed)
+}
+
+/// Up to `limit` keywords from `counts` that start with the keyword being
+/// typed, the last one of the comma separated `input`, most used first.
+pub fn keyword_suggestions<'a>(
+    counts: &'a [(String, usize)],
+    input: &str,
+    limit: usize,
+) -> Vec<&'a str> {
+    let (entered, typed) = input.rsplit_once(',').unwrap_or(("", input));
+    let typed = typed.trim().to_lowercase();
+    if typed.is_empty() {
+        return vec![];
+    }
+    let entered: Vec<&str> = entered.split(',').map(str::trim).collect();
+
+    let mut suggestions: Vec<&(String, usize)> = counts
+        .iter()
+        .filter(|(keyword, _)| {
+            keyword.to_lowercase().starts_with(&typed)
+                && keyword.to_lowercase() != typed
+                && !entered.contains(&keyword.as_str())
+        })
+        .collect();
+    suggestions.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    suggestions
+        .into_iter()
+        .take(limit)
+        .map(|(keyword, _)| keyword.as_str())
+        .collect()
+}
+
+/// `input` with the keyword being typed replaced by `keyword`, ready for the
+/// next one.
+pub fn complete_keyword(input: &str, keyword: &str) -> String {
+    match input.rsplit_once(',') {
+        Some((entered, _)) => format!("{entered}, {keyword}, "),
+        None => format!("{keyword}, "),
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use crate::barcode::code128;
+use crate::book::Book;
+use crate::book_data::format_price;
+use crate::pdf::{Document, Font, Page, mm, text_width};
+
+/// Layout of a sheet of adhesive labels. All lengths are in tenths of a
+/// millimetre, so `700` is 70 mm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelSheet {
+    pub name: String,
+    pub page_width: u32,
+    pub page_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub label_width: u32,
+    pub label_height: u32,
+    /// Distance from the left page edge to the first column.
+    pub left_margin: u32,
+    /// Distance from the top page edge to the first row.
+    pub top_margin: u32,
+    pub column_gap: u32,
+    pub row_gap: u32,
+}
+
+impl Default for LabelSheet {
+    /// Avery Zweckform 3474: 24 labels of 70 × 37 mm on A4.
+    fn default() -> Self {
+        LabelSheet {
+            name: "Avery Zweckform 3474".to_string(),
+            page_width: 2100,
+            page_height: 2970,
+            columns: 3,
+            rows: 8,
+            label_width: 700,
+            label_height: 370,
+            left_margin: 0,
+            top_margin: 5,
+            column_gap: 0,
+            row_gap: 0,
+        }
+    }
+}
+
+impl LabelSheet {
+    /// Checks that the sheet has labels and that they all fit on the page.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.columns == 0 || self.rows == 0 {
+            return Err(format!("label sheet {:?} has no labels", self.name));
+        }
+        if self.label_width == 0 || self.label_height == 0 {
+            return Err(format!("labels of sheet {:?} have no size", self.name));
+        }
+        // Wide enough not to overflow for any lengths in the configuration.
+        let extent = |margin: u32, count: u32, size: u32, gap: u32| {
+            u64::from(margin)
+                + u64::from(count) * u64::from(size)
+                + u64::from(count - 1) * u64::from(gap)
+        };
+        let width = extent(
+            self.left_margin,
+            self.columns,
+            self.label_width,
+            self.column_gap,
+        );
+        let height = extent(self.top_margin, self.rows, self.label_height, self.row_gap);
+        if width > u64::from(self.page_width) || height > u64::from(self.page_height) {
+            return Err(format!(
+                "labels of sheet {:?} don't fit on the page",
+                self.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn labels_path(data_path: &Path) -> PathBuf {
+    data_path.join("labels").join("labels.pdf")
+}
+
+/// Writes one label per book to `path`: ID, price, short title and a Code 128
+/// barcode of the ID. Fails if the labels don't fit on the sheet.
+pub fn write_labels(
+    path: &Path,
+    sheet: &LabelSheet,
+    books: &[&Book],
+) -> Result<(), Box<dyn std::error::Error>> {
+    sheet.validate()?;
+    let tenths = |length: u32| mm(length as f32 / 10.0);
+    let per_page = sheet.columns as usize * sheet.rows as usize;
+
+    let mut document = Document::new();
+    for page_books in books.chunks(per_page) {
+        let mut page = Page::new(tenths(sheet.page_width), tenths(sheet.page_height));
+        for (position, book) in page_books.iter().enumerate() {
+            let column = position as u32 % sheet.columns;
+            let row = position as u32 / sheet.columns;
+            let x = tenths(sheet.left_margin + column * (sheet.label_width + sheet.column_gap));
+            let top = tenths(
+                sheet.page_height - sheet.top_margin - row * (sheet.label_height + sheet.row_gap),
+            );
+            label(
+                &mut page,
+                x,
+                top,
+                tenths(sheet.label_width),
+                tenths(sheet.label_height),
+                book,
+            );
+        }
+        document.add_page(page);
+    }
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    write(path, document.to_bytes())?;
+    Ok(())
+}
+
+/// Draws a label whose top left corner is at `x`, `top`.
+fn label(page: &mut Page, x: f32, top: f32, width: f32, height: f32, book: &Book) {
+    let padding = mm(3.0);
+    let inner_width = width - 2.0 * padding;
+    let id = format!("{:05}", book.id);
+
+    let mut y = top - padding - 9.0;
+    page.text(x + padding, y, 9.0, Font::Bold, &id);
+    if let Some(data) = &book.data {
+        let price = format_price(data.price);
+        page.text_right(x + width - padding, y, 9.0, Font::Bold, &price);
+
+        y -= 9.0;
+        page.text(
+            x + padding,
+            y,
+            7.0,
+            Font::Regular,
+            &shorten(&data.title, inner_width, 7.0),
+        );
+    }
+
+    let Some(widths) = code128(&id) else {
+        return;
+    };
+    // Ten modules of quiet zone on either side.
+    let modules: u32 = widths.iter().map(|width| u32::from(*width)).sum::<u32>() + 20;
+    let module = (inner_width / modules as f32).min(mm(0.5));
+    let bar_height = (y - 4.0 - (top - height + padding)).max(mm(5.0));
+
+    let mut bar_x = x + padding + (inner_width - module * modules as f32) / 2.0 + 10.0 * module;
+    for (index, width) in widths.iter().enumerate() {
+        let bar_width = module * f32::from(*width);
+        if index % 2 == 0 {
+            page.rect(bar_x, y - 4.0 - bar_height, bar_width, bar_height);
+        }
+        bar_x += bar_width;
+    }
+}
+
+/// Cuts `text` so that it fits into `width` points at font size `size`.
+pub fn shorten(text: &str, width: f32, size: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+
+    let mut shortened: String = text.to_string();
+    while !shortened.is_empty() && text_width(&format!("{shortened}…"), size) > width {
+        shortened.pop();
+    }
+    format!("{}…", shortened.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_sheets_that_dont_fit() {
+        assert_eq!(LabelSheet::default().validate(), Ok(()));
+        let no_columns = LabelSheet {
+            columns: 0,
+            ..LabelSheet::default()
+        };
+        assert!(no_columns.validate().is_err());
+        let too_low = LabelSheet {
+            top_margin: 3000,
+            ..LabelSheet::default()
+        };
+        assert!(too_low.validate().is_err());
+        let too_many_rows = LabelSheet {
+            rows: 9,
+            ..LabelSheet::default()
+        };
+        assert!(too_many_rows.validate().is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::book::{Book, save_book};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationKind {
+    Room,
+    #[default]
+    Shelf,
+    Box,
+}
+
+impl LocationKind {
+    pub const ALL: [LocationKind; 3] = [LocationKind::Room, LocationKind::Shelf, LocationKind::Box];
+}
+
+/// A place where books are kept. `BookData::location` holds its name.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Location {
+    pub name: String,
+    #[serde(default)]
+    pub kind: LocationKind,
+    /// Name of the room or shelf this location is part of, empty if none.
+    #[serde(default)]
+    pub parent: String,
+}
+
+impl Location {
+    /// Whether the free text `location` of a book refers to this location,
+    /// ignoring case and whitespace, so "b 3" is found at "B3".
+    pub fn matches(&self, location: &str) -> bool {
+        normalize(&self.name) == normalize(location)
+    }
+}
+
+fn normalize(location: &str) -> String {
+    location
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct Locations {
+    #[serde(default)]
+    locations: Vec<Location>,
+}
+
+fn locations_path(data_path: &Path) -> PathBuf {
+    data_path.join("locations.toml")
+}
+
+pub fn load_locations(data_path: &Path) -> Vec<Location> {
+    match read_to_string(locations_path(data_path)) {
+        Ok(content) => match toml::from_str::<Locations>(&content) {
+            Ok(locations) => locations.locations,
+            Err(err) => {
+                eprintln!("Couldn't parse locations:\n{}", err);
+                vec![]
+            }
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+        Err(err) => {
+            eprintln!("Failed to read locations: {}", err);
+            vec![]
+        }
+    }
+}
+
+pub fn save_locations(
+    data_path: &Path,
+    locations: &[Location],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let serialized = toml::to_string(&Locations {
+        locations: locations.to_vec(),
+    })?;
+    std::fs::write(locations_path(data_path), serialized)?;
+    Ok(())
+}
+
+/// Index into `locations` of the registered location `location` refers to.
+pub fn find_location(locations: &[Location], location: &str) -> Option<usize> {
+    locations
+        .iter()
+        .position(|registered| registered.matches(location))
+}
+
+/// Sets the location of the books at `indices` to `location` and saves them.
+/// A book that can't be saved keeps its location. Returns the number of such
+/// books.
+pub fn move_books(books: &mut [Book], indices: &[usize], location: &str) -> usize {
+    let mut failed = 0;
+    for index in indices {
+        let book = &mut books[*index];
+        let Some(data) = book.data.as_mut() else {
+            continue;
+        };
+        let old = std::mem::replace(&mut data.location, location.to_string());
+        if let Err(err) = save_book(book) {
+            eprintln!("Failed to save book {:05}: {}", book.id, err);
+            book.data.as_mut().unwrap().location = old;
+            failed += 1;
+        }
+    }
+    failed
+}
+
+/// Renames the location at `index` in the register, in the locations it
+/// contains and in every book kept there. Fails if another location has the
+/// name already, or if a book can't be saved; then the books already saved
+/// are moved back and the register is left as it is.
+pub fn rename_location(
+    locations: &mut [Location],
+    books: &mut [Book],
+    index: usize,
+    name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if find_location(locations, name).is_some_and(|other| other != index) {
+        return Err(format!("there is a location named {name:?} already").into());
+    }
+
+    let old = locations[index].clone();
+    // Each book with its location as written, which may differ from the
+    // register in case or spacing.
+    let kept: Vec<(usize, String)> = books
+        .iter()
+        .enumerate()
+        .filter_map(|(index, book)| Some((index, book.data.as_ref()?.location.clone())))
+        .filter(|(_, location)| old.matches(location))
+        .collect();
+    for (moved, (book_index, _)) in kept.iter().enumerate() {
+        let book = &mut books[*book_index];
+        book.data.as_mut().unwrap().location = name.to_string();
+        if let Err(err) = save_book(book) {
+            let id = book.id;
+            book.data.as_mut().unwrap().location = kept[moved].1.clone();
+            for (book_index, location) in &kept[..moved] {
+                let book = &mut books[*book_index];
+                book.data.as_mut().unwrap().location = location.clone();
+                if let Err(err) = save_book(book) {
+                    eprintln!("Failed to move book {:05} back: {}", book.id, err);
+                }
+            }
+            return Err(format!("book {id:05} could not be saved: {err}").into());
+        }
+    }
+
+    for location in locations.iter_mut() {
+        if old.matches(&location.parent) {
+            location.parent = name.to_string();
+        }
+    }
+    locations[index].name = name.to_string();
+    Ok(())
+}
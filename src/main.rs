@@ -1,15 +1,46 @@
 // SPDX-License-Identifier: MIT
 
-use std::path::Path;
 
 mod app;
 
+pub mod acquisition;
+pub mod audit;
+pub mod authority;
+pub mod barcode;
 pub mod book;
 pub mod book_data;
+pub mod bulk;
+pub mod changelog;
+mod cli;
 mod config;
+pub mod csv;
+pub mod customer;
+pub mod history;
 mod i18n;
+pub mod imprint;
+pub mod invoice;
+pub mod keyword;
+pub mod labels;
+pub mod location;
+pub mod pdf;
+pub mod physical;
+pub mod postage;
+pub mod records;
+pub mod sale;
+pub mod scan;
+pub mod statistics;
+pub mod status;
+pub mod validate;
+pub mod valuation;
 
 fn main() -> cosmic::iced::Result {
+    // Subcommands run headless, e.g. from cron.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| cli::is_command(arg)) {
+        let config = config::Config::load(<app::AppModel as cosmic::Application>::APP_ID);
+        std::process::exit(cli::run(&config, &args));
+    }
+
     // Get the system's preferred languages.
     let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
 
@@ -0,0 +1,235 @@
+//! A minimal PDF writer for the documents the app prints: text in Helvetica and
+//! filled rectangles on any number of pages. Coordinates are in points from the
+//! bottom left corner of the page.
+
+use std::fmt::Write;
+
+pub const A4_WIDTH: f32 = 595.28;
+pub const A4_HEIGHT: f32 = 841.89;
+
+/// Converts millimetres to points.
+pub fn mm(millimetres: f32) -> f32 {
+    millimetres * 72.0 / 25.4
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+pub struct Page {
+    width: f32,
+    height: f32,
+    content: String,
+}
+
+impl Page {
+    pub fn new(width: f32, height: f32) -> Self {
+        Page {
+            width,
+            height,
+            content: String::new(),
+        }
+    }
+
+    pub fn a4() -> Self {
+        Page::new(A4_WIDTH, A4_HEIGHT)
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Draws `text` with its baseline starting at `x`, `y`.
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let font = match font {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        };
+        let _ = writeln!(
+            self.content,
+            "BT /{font} {size:.2} Tf {x:.2} {y:.2} Td ({}) Tj ET",
+            escape(&encode(text))
+        );
+    }
+
+    /// Draws `text` so that it ends at `x`.
+    pub fn text_right(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        self.text(x - text_width(text, size), y, size, font, text);
+    }
+
+    /// Fills a black rectangle with its lower left corner at `x`, `y`.
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(self.content, "{x:.2} {y:.2} {width:.2} {height:.2} re f");
+    }
+
+    /// Draws a thin horizontal line from `x1` to `x2`.
+    pub fn hline(&mut self, x1: f32, x2: f32, y: f32) {
+        let _ = writeln!(self.content, "0.5 w {x1:.2} {y:.2} m {x2:.2} {y:.2} l S");
+    }
+}
+
+#[derive(Default)]
+pub struct Document {
+    pages: Vec<Page>,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Document::default()
+    }
+
+    pub fn add_page(&mut self, page: Page) {
+        self.pages.push(page);
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Objects 1 and 2 are the catalog and the page tree, 3 and 4 the fonts,
+        // followed by a page and a content stream object for every page.
+        let mut objects: Vec<Vec<u8>> = vec![];
+
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+
+        let kids: Vec<String> = (0..self.pages.len())
+            .map(|index| format!("{} 0 R", 5 + index * 2))
+            .collect();
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            )
+            .into_bytes(),
+        );
+
+        for font in ["Helvetica", "Helvetica-Bold"] {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{font} /Encoding /WinAnsiEncoding >>"
+                )
+                .into_bytes(),
+            );
+        }
+
+        for (index, page) in self.pages.iter().enumerate() {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    page.width,
+                    page.height,
+                    6 + index * 2
+                )
+                .into_bytes(),
+            );
+
+            let mut stream = format!("<< /Length {} >>\nstream\n", page.content.len()).into_bytes();
+            stream.extend_from_slice(page.content.as_bytes());
+            stream.extend_from_slice(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut bytes = b"%PDF-1.4\n".to_vec();
+        let mut offsets = vec![];
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(bytes.len());
+            bytes.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            bytes.extend_from_slice(object);
+            bytes.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = bytes.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(trailer, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        );
+        bytes.extend_from_slice(trailer.as_bytes());
+
+        bytes
+    }
+}
+
+/// Width of `text` in points, using the Helvetica metrics.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = encode(text).iter().map(|byte| char_width(*byte)).sum();
+    units as f32 * size / 1000.0
+}
+
+/// Converts `text` to WinAnsiEncoding, replacing characters it lacks with `?`.
+/// The isolation marks Fluent puts around arguments are dropped.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter(|c| !matches!(c, '\u{2068}' | '\u{2069}'))
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '‚' => 0x82,
+            '„' => 0x84,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        })
+        .collect()
+}
+
+fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::new();
+    for byte in bytes {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(*byte as char);
+            }
+            0x20..=0x7e => escaped.push(*byte as char),
+            _ => {
+                let _ = write!(escaped, "\\{byte:03o}");
+            }
+        }
+    }
+    escaped
+}
+
+/// Helvetica glyph widths in thousandths of the font size.
+fn char_width(byte: u8) -> u32 {
+    #[rustfmt::skip]
+    const ASCII: [u32; 95] = [
+        // ' ' to '/'
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+        // '0' to '?'
+        556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+        // '@' to 'O'
+        1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+        // 'P' to '_'
+        667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+        // '`' to 'o'
+        333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+        // 'p' to '~'
+        556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+    ];
+
+    match byte {
+        0x20..=0x7e => ASCII[(byte - 0x20) as usize],
+        0xc4 => 667,  // Ä
+        0xd6 => 778,  // Ö
+        0xdc => 722,  // Ü
+        0xdf => 611,  // ß
+        0x96 => 556,  // –
+        0x97 => 1000, // —
+        0xa7 => 556,  // §
+        0xb0 => 400,  // °
+        _ => 556,
+    }
+}
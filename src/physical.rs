@@ -0,0 +1,212 @@
+//! Physical description of a copy: pagination and format. Both are stored as
+//! the text the trade writes, like "XII, 345 S., 12 Taf." and "8°".
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A pagination statement as written, with its parts read from it. The text is
+/// kept as it is, so parts and units the parser doesn't know survive.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub struct Pagination {
+    /// The statement as written, like "XII, 345 S., 12 Taf.".
+    pub text: String,
+    /// Preliminary pages in roman numerals, like "XII".
+    pub roman: String,
+    /// Main page count.
+    pub pages: Option<u32>,
+    /// Plates printed separately from the text.
+    pub plates: Option<u32>,
+    /// Everything else, like "[4] Bl." or "1 Karte".
+    pub other: String,
+}
+
+impl Pagination {
+    pub fn parse(text: &str) -> Self {
+        let mut pagination = Pagination {
+            text: text.trim().to_string(),
+            ..Pagination::default()
+        };
+        let mut other = vec![];
+        for part in text
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            if pagination.roman.is_empty() && is_roman(part) {
+                pagination.roman = part.to_uppercase();
+                continue;
+            }
+            match (
+                count(part, &PAGE_UNITS, true),
+                count(part, &PLATE_UNITS, false),
+            ) {
+                (Some(pages), _) if pagination.pages.is_none() => pagination.pages = Some(pages),
+                (_, Some(plates)) if pagination.plates.is_none() => {
+                    pagination.plates = Some(plates)
+                }
+                _ => other.push(part),
+            }
+        }
+        pagination.other = other.join(", ");
+        pagination
+    }
+}
+
+/// Ways of writing pages after the count.
+const PAGE_UNITS: [&str; 5] = ["S.", "Seiten", "pp.", "p.", "pages"];
+const PLATE_UNITS: [&str; 4] = ["Taf.", "Tafeln", "plates", "pl."];
+
+/// The number of a part like "345 S." with one of `units`, or of a bare
+/// number if `bare` is set.
+fn count(part: &str, units: &[&str], bare: bool) -> Option<u32> {
+    let (number, unit) = part.split_once(' ').unwrap_or((part, ""));
+    let number = number.parse().ok()?;
+    let unit = unit.trim().trim_end_matches('.');
+    let known = |known: &&str| known.trim_end_matches('.').eq_ignore_ascii_case(unit);
+    match unit.is_empty() {
+        true => bare.then_some(number),
+        false => units.iter().any(known).then_some(number),
+    }
+}
+
+fn is_roman(part: &str) -> bool {
+    part.chars().all(|c| {
+        matches!(
+            c.to_ascii_uppercase(),
+            'I' | 'V' | 'X' | 'L' | 'C' | 'D' | 'M'
+        )
+    })
+}
+
+impl fmt::Display for Pagination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Smallest and largest main page count of a filter like "100-300", `None` if
+/// `text` is no such range.
+pub fn page_range(text: &str) -> Option<(u32, u32)> {
+    let (min, max) = text.split_once('-')?;
+    Some((min.trim().parse().ok()?, max.trim().parse().ok()?))
+}
+
+impl From<String> for Pagination {
+    fn from(text: String) -> Self {
+        Pagination::parse(&text)
+    }
+}
+
+impl From<Pagination> for String {
+    fn from(pagination: Pagination) -> Self {
+        pagination.to_string()
+    }
+}
+
+/// Size of a book by the folding of its sheets, or by the height of the spine.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum BookFormat {
+    #[default]
+    Unknown,
+    Folio,
+    Quarto,
+    Octavo,
+    Duodecimo,
+    Sextodecimo,
+    /// Height of the spine in centimetres.
+    Height(u16),
+    /// Text from records written before formats were a fixed list.
+    Other(String),
+}
+
+impl BookFormat {
+    /// The foldings offered in the book form, largest first.
+    pub const FOLDINGS: [BookFormat; 5] = [
+        BookFormat::Folio,
+        BookFormat::Quarto,
+        BookFormat::Octavo,
+        BookFormat::Duodecimo,
+        BookFormat::Sextodecimo,
+    ];
+
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        let compact: String = text
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '.')
+            .flat_map(char::to_lowercase)
+            .collect();
+        match compact.as_str() {
+            "" => BookFormat::Unknown,
+            "2°" | "2o" | "fol" | "folio" => BookFormat::Folio,
+            "4°" | "4o" | "4to" | "quart" | "quarto" => BookFormat::Quarto,
+            "8°" | "8o" | "8vo" | "oktav" | "octavo" => BookFormat::Octavo,
+            "12°" | "12o" | "12mo" | "duodez" | "duodecimo" => BookFormat::Duodecimo,
+            "16°" | "16o" | "16mo" | "sedez" | "sextodecimo" => BookFormat::Sextodecimo,
+            _ => match compact.strip_suffix("cm").and_then(|cm| cm.parse().ok()) {
+                Some(cm) if cm > 0 => BookFormat::Height(cm),
+                _ => BookFormat::Other(text.to_string()),
+            },
+        }
+    }
+
+    /// Height of the spine if it was measured.
+    pub fn height(&self) -> Option<u16> {
+        match self {
+            BookFormat::Height(cm) => Some(*cm),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BookFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookFormat::Unknown => Ok(()),
+            BookFormat::Folio => write!(f, "2°"),
+            BookFormat::Quarto => write!(f, "4°"),
+            BookFormat::Octavo => write!(f, "8°"),
+            BookFormat::Duodecimo => write!(f, "12°"),
+            BookFormat::Sextodecimo => write!(f, "16°"),
+            BookFormat::Height(cm) => write!(f, "{cm} cm"),
+            BookFormat::Other(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+impl From<String> for BookFormat {
+    fn from(text: String) -> Self {
+        BookFormat::parse(&text)
+    }
+}
+
+impl From<BookFormat> for String {
+    fn from(format: BookFormat) -> Self {
+        format.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pagination() {
+        let pagination = Pagination::parse("XII, 345 S., 12 Taf.");
+        assert_eq!(pagination.roman, "XII");
+        assert_eq!(pagination.pages, Some(345));
+        assert_eq!(pagination.plates, Some(12));
+        assert_eq!(pagination.other, "");
+        assert_eq!(pagination.to_string(), "XII, 345 S., 12 Taf.");
+    }
+
+    #[test]
+    fn keeps_pagination_as_written() {
+        let pagination = Pagination::parse("345 pp., XII");
+        assert_eq!(pagination.pages, Some(345));
+        assert_eq!(pagination.roman, "XII");
+        assert_eq!(pagination.to_string(), "345 pp., XII");
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::book_data::BookData;
+
+/// Destination region a tariff applies to.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Zone {
+    #[default]
+    Domestic,
+    Eu,
+    World,
+}
+
+impl Zone {
+    pub const ALL: [Zone; 3] = [Zone::Domestic, Zone::Eu, Zone::World];
+}
+
+/// A shipping product of a carrier with its price for one parcel.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Tariff {
+    pub carrier: String,
+    pub name: String,
+    pub zone: Zone,
+    /// Heaviest parcel accepted, in grams including packaging.
+    pub max_weight: u32,
+    /// Price in cents.
+    pub price: u32,
+}
+
+impl Tariff {
+    fn new(carrier: &str, name: &str, zone: Zone, max_weight: u32, price: u32) -> Self {
+        Tariff {
+            carrier: carrier.to_string(),
+            name: name.to_string(),
+            zone,
+            max_weight,
+            price,
+        }
+    }
+}
+
+/// Rate tables used to suggest postage.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Postage {
+    /// Weight of envelope or box and padding in grams, added to every parcel.
+    pub packaging_weight: u32,
+    pub tariffs: Vec<Tariff>,
+}
+
+impl Default for Postage {
+    /// German rates at the time of writing. They change every year or two, so
+    /// the tables are kept in the config rather than in code that uses them.
+    fn default() -> Self {
+        Postage {
+            packaging_weight: 80,
+            tariffs: vec![
+                Tariff::new(
+                    "Deutsche Post",
+                    "Büchersendung 500",
+                    Zone::Domestic,
+                    500,
+                    195,
+                ),
+                Tariff::new(
+                    "Deutsche Post",
+                    "Büchersendung 1000",
+                    Zone::Domestic,
+                    1000,
+                    270,
+                ),
+                Tariff::new("Deutsche Post", "Warensendung", Zone::Domestic, 1000, 280),
+                Tariff::new("DHL", "Päckchen S", Zone::Domestic, 2000, 399),
+                Tariff::new("DHL", "Paket 5 kg", Zone::Domestic, 5000, 699),
+                Tariff::new("DHL", "Paket 10 kg", Zone::Domestic, 10000, 1049),
+                Tariff::new(
+                    "Deutsche Post",
+                    "Bücher- und Warensendung International",
+                    Zone::Eu,
+                    2000,
+                    880,
+                ),
+                Tariff::new("DHL", "Päckchen International", Zone::Eu, 2000, 1049),
+                Tariff::new("DHL", "Paket International 5 kg", Zone::Eu, 5000, 1899),
+                Tariff::new(
+                    "Deutsche Post",
+                    "Bücher- und Warensendung International",
+                    Zone::World,
+                    2000,
+                    1600,
+                ),
+                Tariff::new("DHL", "Päckchen International", Zone::World, 2000, 1749),
+                Tariff::new("DHL", "Paket International 5 kg", Zone::World, 5000, 3699),
+            ],
+        }
+    }
+}
+
+impl Postage {
+    /// Weight in grams of a parcel containing `books`.
+    pub fn parcel_weight<'a>(&self, books: impl IntoIterator<Item = &'a BookData>) -> u32 {
+        self.packaging_weight
+            + books
+                .into_iter()
+                .map(|data| u32::from(data.weight))
+                .sum::<u32>()
+    }
+
+    /// Tariffs that can carry a parcel of `weight` grams to `zone`, cheapest first.
+    pub fn options(&self, zone: Zone, weight: u32) -> Vec<&Tariff> {
+        let mut options: Vec<&Tariff> = self
+            .tariffs
+            .iter()
+            .filter(|tariff| tariff.zone == zone && tariff.max_weight >= weight)
+            .collect();
+        options.sort_by_key(|tariff| tariff.price);
+        options
+    }
+
+    /// The cheapest way to send `books` to `zone` in one parcel, if there is any.
+    pub fn cheapest<'a>(
+        &self,
+        zone: Zone,
+        books: impl IntoIterator<Item = &'a BookData>,
+    ) -> Option<&Tariff> {
+        self.options(zone, self.parcel_weight(books))
+            .first()
+            .copied()
+    }
+}
@@ -0,0 +1,99 @@
+//! Numbered TOML records (`00001.toml`, `00002.toml`, ...) kept in a subdirectory
+//! of the data directory, such as the sales ledger or the customer register.
+
+use regex::Regex;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs::{File, OpenOptions, create_dir_all};
+use std::io::{Read, Write};
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Loads all records in `dir` together with their number, sorted by number.
+/// Files that can't be read or parsed are reported and skipped.
+pub fn load_records<T: DeserializeOwned>(dir: &Path) -> Vec<(u32, T)> {
+    let filename_regex = Regex::new(r"^\d{5}\.toml$").unwrap();
+
+    let mut records = vec![];
+    for entry in WalkDir::new(dir)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+    {
+        let Some(file_name) = entry.file_name().to_str() else {
+            continue;
+        };
+        if !entry.file_type().is_file() || !filename_regex.is_match(file_name) {
+            continue;
+        }
+
+        let mut file_content = String::new();
+        if let Err(err) =
+            File::open(entry.path()).and_then(|mut f| f.read_to_string(&mut file_content))
+        {
+            eprintln!("Failed to read record {:#?}: {}", entry.path(), err);
+            continue;
+        }
+
+        match toml::from_str::<T>(&file_content) {
+            Ok(record) => records.push((file_name[..5].parse().unwrap(), record)),
+            Err(err) => eprintln!("Couldn't parse record {:#?}:\n{}", entry.path(), err),
+        }
+    }
+
+    records.sort_by_key(|(id, _)| *id);
+    records
+}
+
+/// Writes `record` under the next free number in `dir` and returns that number.
+pub fn create_record<T: Serialize>(
+    dir: &Path,
+    record: &T,
+) -> Result<u32, Box<dyn std::error::Error>> {
+    create_dir_all(dir)?;
+
+    let serialized = toml::to_string(record)?;
+    let mut id = next_id(dir);
+    loop {
+        let result = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(format!("{id:05}.toml")));
+
+        match result {
+            Ok(mut file) => {
+                file.write_all(serialized.as_bytes())?;
+                return Ok(id);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => id += 1,
+            Err(err) => return Err(Box::new(err)),
+        }
+    }
+}
+
+/// Overwrites the record with number `id` in `dir`.
+pub fn write_record<T: Serialize>(
+    dir: &Path,
+    id: u32,
+    record: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    create_dir_all(dir)?;
+    std::fs::write(dir.join(format!("{id:05}.toml")), toml::to_string(record)?)?;
+    Ok(())
+}
+
+/// One above the highest number of a record in `dir`, 1 if there is none.
+pub fn next_id(dir: &Path) -> u32 {
+    let filename_regex = Regex::new(r"^(\d{5})\.toml$").unwrap();
+
+    WalkDir::new(dir)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_str()?;
+            filename_regex.captures(file_name)?[1].parse::<u32>().ok()
+        })
+        .max()
+        .map_or(1, |last| last + 1)
+}
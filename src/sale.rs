@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::records::{create_record, load_records, write_record};
+
+/// Where a sale was made.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Booklooker,
+    Zvab,
+    #[default]
+    Shop,
+    Fair,
+    Other,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 5] = [
+        Channel::Booklooker,
+        Channel::Zvab,
+        Channel::Shop,
+        Channel::Fair,
+        Channel::Other,
+    ];
+}
+
+/// One book sold as part of a sale, with the price achieved for it in cents.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SaleItem {
+    pub book_id: u32,
+    pub price: u32,
+    /// Day the copy came back. Returned copies stay on the invoice but no
+    /// longer count as sold.
+    #[serde(default)]
+    pub returned: Option<NaiveDate>,
+}
+
+/// A sale as stored in the `sales/` ledger. Amounts are in cents.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Sale {
+    /// Taken from the file name, not stored in the file.
+    #[serde(skip)]
+    pub id: u32,
+    pub date: NaiveDate,
+    pub channel: Channel,
+    pub items: Vec<SaleItem>,
+    pub shipping: u32,
+    /// Name of the buyer as entered, also for buyers not in the customer register.
+    #[serde(default)]
+    pub customer: String,
+    /// Number of the buyer in the customer register.
+    #[serde(default)]
+    pub customer_id: Option<u32>,
+}
+
+impl Sale {
+    pub fn contains(&self, book_id: u32) -> bool {
+        self.items.iter().any(|item| item.book_id == book_id)
+    }
+
+    /// Sum of item prices, without shipping.
+    pub fn revenue(&self) -> u32 {
+        self.items.iter().map(|item| item.price).sum()
+    }
+
+    /// The items that were not returned.
+    pub fn kept_items(&self) -> impl Iterator<Item = &SaleItem> {
+        self.items.iter().filter(|item| item.returned.is_none())
+    }
+
+    /// Sum of the prices of the items that were not returned, without shipping.
+    pub fn kept_revenue(&self) -> u32 {
+        self.kept_items().map(|item| item.price).sum()
+    }
+}
+
+pub fn sales_path(data_path: &Path) -> PathBuf {
+    data_path.join("sales")
+}
+
+pub fn load_sales(data_path: &Path) -> Vec<Sale> {
+    load_records(&sales_path(data_path))
+        .into_iter()
+        .map(|(id, sale)| Sale { id, ..sale })
+        .collect()
+}
+
+/// Writes `sale` to the ledger under the next free number, which is stored in
+/// `sale.id`.
+pub fn record_sale(data_path: &Path, sale: &mut Sale) -> Result<(), Box<dyn std::error::Error>> {
+    sale.id = create_record(&sales_path(data_path), sale)?;
+    Ok(())
+}
+
+/// Marks the copy of `book_id` sold last and not returned yet as returned on
+/// `date` and rewrites its sale in the ledger. Returns `false` if no such sale
+/// was recorded, as for books sold before the ledger was kept.
+pub fn record_return(
+    data_path: &Path,
+    sales: &mut [Sale],
+    book_id: u32,
+    date: NaiveDate,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let Some(sale) = sales
+        .iter_mut()
+        .rev()
+        .find(|sale| sale.kept_items().any(|item| item.book_id == book_id))
+    else {
+        return Ok(false);
+    };
+
+    let mut returned = sale.clone();
+    let item = returned
+        .items
+        .iter_mut()
+        .find(|item| item.book_id == book_id && item.returned.is_none())
+        .unwrap();
+    item.returned = Some(date);
+    write_record(&sales_path(data_path), returned.id, &returned)?;
+    *sale = returned;
+    Ok(true)
+}
@@ -0,0 +1,137 @@
+//! Lookup of codes read by a barcode scanner: the IDs printed on shelf labels
+//! and the ISBN barcodes on the books themselves.
+
+use std::time::{Duration, Instant};
+
+use crate::book::Book;
+
+/// Scanners type a whole code within a few milliseconds per character; a
+/// longer pause means a person is typing.
+const MAX_KEY_INTERVAL: Duration = Duration::from_millis(50);
+/// Shortest code worth looking up: the five digits of an ID label.
+const MIN_CODE_LENGTH: usize = 5;
+
+/// Collects the characters of a keyboard burst until Enter.
+#[derive(Default)]
+pub struct ScanBuffer {
+    code: String,
+    last_key: Option<Instant>,
+}
+
+impl ScanBuffer {
+    pub fn push(&mut self, c: char, at: Instant) {
+        if self
+            .last_key
+            .is_some_and(|last_key| at.duration_since(last_key) > MAX_KEY_INTERVAL)
+        {
+            self.code.clear();
+        }
+        self.code.push(c);
+        self.last_key = Some(at);
+    }
+
+    /// Ends the burst at Enter. Returns the code if it was typed fast enough to
+    /// come from a scanner.
+    pub fn finish(&mut self, at: Instant) -> Option<String> {
+        let code = std::mem::take(&mut self.code);
+        let last_key = self.last_key.take()?;
+
+        (at.duration_since(last_key) <= MAX_KEY_INTERVAL && code.len() >= MIN_CODE_LENGTH)
+            .then_some(code)
+    }
+}
+
+/// Normalizes an ISBN-10, ISBN-13 or EAN-13 starting with 978/979 to ISBN-13
+/// digits. Returns `None` if `code` is not a valid ISBN.
+pub fn normalize_isbn(code: &str) -> Option<String> {
+    let code: String = code
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    // The lengths below count bytes, which are only digits for ASCII.
+    if !code.is_ascii() {
+        return None;
+    }
+
+    match code.len() {
+        10 => {
+            let (body, check) = code.split_at(9);
+            if !body.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let sum: u32 = body
+                .chars()
+                .zip((2..=10).rev())
+                .map(|(c, weight)| c.to_digit(10).unwrap() * weight)
+                .sum();
+            let expected = (11 - sum % 11) % 11;
+            let check = match check {
+                "X" => 10,
+                digit => digit.parse().ok()?,
+            };
+            (expected == check).then(|| isbn13(&format!("978{body}")))
+        }
+        13 => {
+            if !code.chars().all(|c| c.is_ascii_digit())
+                || !(code.starts_with("978") || code.starts_with("979"))
+            {
+                return None;
+            }
+            (isbn13(&code[..12]) == code).then_some(code)
+        }
+        _ => None,
+    }
+}
+
+/// Appends the check digit to twelve ISBN-13 digits.
+fn isbn13(body: &str) -> String {
+    let sum: u32 = body
+        .chars()
+        .enumerate()
+        .map(|(position, c)| c.to_digit(10).unwrap() * if position % 2 == 0 { 1 } else { 3 })
+        .sum();
+    format!("{body}{}", (10 - sum % 10) % 10)
+}
+
+/// Index into `books` of the book with the scanned ID label or ISBN.
+pub fn lookup(code: &str, books: &[Book]) -> Option<usize> {
+    let code = code.trim();
+
+    if code.len() <= 5 && code.chars().all(|c| c.is_ascii_digit()) {
+        let id: u32 = code.parse().ok()?;
+        return books.iter().position(|book| book.id == id);
+    }
+
+    let isbn = normalize_isbn(code)?;
+    books.iter().position(|book| {
+        book.data
+            .as_ref()
+            .and_then(|data| normalize_isbn(&data.isbn))
+            .is_some_and(|book_isbn| book_isbn == isbn)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_isbn() {
+        assert_eq!(
+            normalize_isbn("3-16-148410-X").as_deref(),
+            Some("9783161484100")
+        );
+        assert_eq!(
+            normalize_isbn("978-3-16-148410-0").as_deref(),
+            Some("9783161484100")
+        );
+        assert_eq!(normalize_isbn("3-16-148410-0"), None);
+    }
+
+    #[test]
+    fn rejects_non_ascii() {
+        assert_eq!(normalize_isbn("12345678é"), None);
+        assert_eq!(normalize_isbn("123456789é"), None);
+    }
+}
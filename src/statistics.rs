@@ -0,0 +1,159 @@
+//! Figures about the stock and the sales shown on the Statistics page.
+
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate};
+use std::collections::BTreeMap;
+
+use crate::authority::{Authority, AuthorityKind, preferred_name};
+use crate::book::Book;
+use crate::book_data::Role;
+use crate::sale::Sale;
+use crate::status::Status;
+
+/// Number of books and their value in cents.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tally {
+    pub count: u32,
+    pub value: u32,
+}
+
+impl Tally {
+    fn add(&mut self, count: u32, value: u32) {
+        self.count += count;
+        self.value += value;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Statistics {
+    /// Copies on hand that can be sold, at list price.
+    pub stock: Tally,
+    pub by_category: BTreeMap<u16, Tally>,
+    pub by_condition: BTreeMap<u8, Tally>,
+    pub by_language: BTreeMap<String, Tally>,
+    /// Keyed by the preferred form of the author's name from the authority
+    /// register, so that variants of a name count together.
+    pub by_author: BTreeMap<String, Tally>,
+    /// Average time since the books in stock were added, in days.
+    pub average_days_in_stock: Option<u32>,
+    /// Average time since the books in stock were published, in years.
+    pub average_age: Option<u32>,
+    /// Books sold and revenue per year and month.
+    pub sales_per_month: BTreeMap<(i32, u32), Tally>,
+    /// Indices into `books` of the books in stock for more than the given number
+    /// of years, longest first.
+    pub slow_movers: Vec<usize>,
+}
+
+/// Day `book` was entered into stock: recorded for new books, otherwise taken
+/// from the creation time of its file.
+pub fn in_stock_since(book: &Book) -> Option<NaiveDate> {
+    if let Some(added) = book.data.as_ref().and_then(|data| data.added) {
+        return Some(added);
+    }
+    let created = book.file.metadata().ok()?.created().ok()?;
+    Some(DateTime::<Local>::from(created).date_naive())
+}
+
+impl Statistics {
+    pub fn compute(
+        books: &[Book],
+        sales: &[Sale],
+        authorities: &[Authority],
+        today: NaiveDate,
+        slow_after_years: u32,
+    ) -> Self {
+        let mut statistics = Statistics::default();
+        let mut days_in_stock = vec![];
+        let mut ages = vec![];
+        let mut slow_movers = vec![];
+
+        for (index, book) in books.iter().enumerate() {
+            let Some(data) = &book.data else {
+                continue;
+            };
+            if !data.in_stock() || matches!(data.status, Status::Sold | Status::Withdrawn) {
+                continue;
+            }
+
+            let count = if data.unlimited { 1 } else { data.quantity };
+            let value = count * data.price;
+            statistics.stock.add(count, value);
+            statistics
+                .by_category
+                .entry(data.category)
+                .or_default()
+                .add(count, value);
+            statistics
+                .by_condition
+                .entry(data.condition)
+                .or_default()
+                .add(count, value);
+            statistics
+                .by_language
+                .entry(data.language.trim().to_string())
+                .or_default()
+                .add(count, value);
+            let mut authors: Vec<String> = data
+                .contributors()
+                .into_iter()
+                .filter(|contributor| contributor.role == Role::Author)
+                .map(|contributor| {
+                    preferred_name(authorities, AuthorityKind::Person, &contributor.name)
+                })
+                .collect();
+            authors.sort();
+            authors.dedup();
+            for author in authors {
+                statistics
+                    .by_author
+                    .entry(author)
+                    .or_default()
+                    .add(count, value);
+            }
+
+            if let Some(year) = data
+                .year
+                .year()
+                .filter(|year| i32::from(*year) <= today.year())
+            {
+                ages.push((today.year() - i32::from(year)) as u32);
+            }
+            if let Some(since) = in_stock_since(book) {
+                let days = (today - since).num_days().max(0) as u32;
+                days_in_stock.push(days);
+                if since
+                    .checked_add_months(Months::new(12 * slow_after_years))
+                    .is_some_and(|slow_from| slow_from <= today)
+                {
+                    slow_movers.push((since, index));
+                }
+            }
+        }
+
+        statistics.average_days_in_stock = average(&days_in_stock);
+        statistics.average_age = average(&ages);
+        slow_movers.sort();
+        statistics.slow_movers = slow_movers.into_iter().map(|(_, index)| index).collect();
+
+        for sale in sales
+            .iter()
+            .filter(|sale| sale.kept_items().next().is_some())
+        {
+            statistics
+                .sales_per_month
+                .entry((sale.date.year(), sale.date.month()))
+                .or_default()
+                .add(sale.kept_items().count() as u32, sale.kept_revenue());
+        }
+
+        statistics
+    }
+}
+
+fn average(values: &[u32]) -> Option<u32> {
+    if values.is_empty() {
+        return None;
+    }
+    let sum: u64 = values.iter().map(|value| u64::from(*value)).sum();
+    Some((sum / values.len() as u64) as u32)
+}
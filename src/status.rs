@@ -0,0 +1,56 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Where a book stands in its life as a piece of stock.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    #[default]
+    Available,
+    Reserved,
+    Sold,
+    Withdrawn,
+    /// Sold once, came back and is for sale again.
+    Returned,
+}
+
+impl Status {
+    pub const ALL: [Status; 5] = [
+        Status::Available,
+        Status::Reserved,
+        Status::Sold,
+        Status::Withdrawn,
+        Status::Returned,
+    ];
+
+    /// Statuses that can be reached from `self`.
+    pub fn transitions(self) -> &'static [Status] {
+        match self {
+            Status::Available | Status::Returned => {
+                &[Status::Reserved, Status::Sold, Status::Withdrawn]
+            }
+            Status::Reserved => &[Status::Available, Status::Sold, Status::Withdrawn],
+            Status::Sold => &[Status::Returned],
+            Status::Withdrawn => &[Status::Available],
+        }
+    }
+
+    pub fn can_become(self, next: Status) -> bool {
+        self.transitions().contains(&next)
+    }
+
+    /// Whether a book in this status can be sold.
+    pub fn is_for_sale(self) -> bool {
+        matches!(
+            self,
+            Status::Available | Status::Returned | Status::Reserved
+        )
+    }
+}
+
+/// A single entry in a book's status history.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct StatusChange {
+    pub status: Status,
+    pub at: DateTime<Local>,
+}
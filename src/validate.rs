@@ -0,0 +1,197 @@
+//! Checks of the data directory for problems that marketplaces reject.
+
+use chrono::{Datelike, Local};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::book::{book_file_names, read_book_from_file};
+use crate::book_data::BookData;
+use crate::physical::BookFormat;
+use crate::scan::normalize_isbn;
+
+/// Files in the data directory besides the books and their covers.
+const KNOWN_FILES: [&str; 3] = ["locations.toml", "audit.toml", "authorities.toml"];
+/// Nothing in stock was printed before Gutenberg.
+const EARLIEST_YEAR: u16 = 1450;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    ParseError,
+    UnexpectedFile,
+    DuplicateIsbn,
+    ZeroPrice,
+    InvalidYear,
+    EmptyTitle,
+    KeywordWhitespace,
+    MissingCover,
+    UnlistedFormat,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Issue {
+    /// File name relative to the data directory.
+    pub file: String,
+    pub kind: IssueKind,
+    pub message: String,
+    /// Whether `fix` can resolve the issue without asking.
+    pub fixable: bool,
+}
+
+impl Issue {
+    fn new(file: &str, kind: IssueKind, message: String) -> Self {
+        Issue {
+            file: file.to_string(),
+            kind,
+            message,
+            fixable: kind == IssueKind::KeywordWhitespace,
+        }
+    }
+}
+
+/// Checks all files in `data_path` and returns the problems found, in file order.
+pub fn validate(data_path: &Path) -> Vec<Issue> {
+    let mut issues = vec![];
+    let book_files = book_file_names(data_path);
+
+    let mut isbns: HashMap<String, Vec<String>> = HashMap::new();
+    // Cover images are referenced relative to the data directory.
+    let mut covers: HashSet<PathBuf> = HashSet::new();
+    for file_name in &book_files {
+        let read_result = File::open(data_path.join(file_name))
+            .map_err(|err| (Box::new(err) as Box<dyn std::error::Error>, String::new()))
+            .and_then(|mut file| read_book_from_file(&mut file));
+
+        match read_result {
+            Ok(data) => {
+                issues.extend(check_book(data_path, file_name, &data));
+                if !data.cover.trim().is_empty() {
+                    covers.insert(data_path.join(data.cover.trim()));
+                }
+                let isbn = data.isbn.trim();
+                if !isbn.is_empty() {
+                    let isbn = normalize_isbn(isbn).unwrap_or_else(|| isbn.to_string());
+                    isbns.entry(isbn).or_default().push(file_name.clone());
+                }
+            }
+            Err((err, _)) => {
+                issues.push(Issue::new(
+                    file_name,
+                    IssueKind::ParseError,
+                    err.to_string(),
+                ));
+            }
+        }
+    }
+
+    for (isbn, files) in isbns.iter().filter(|(_, files)| files.len() > 1) {
+        for file_name in files {
+            let others: Vec<&str> = files
+                .iter()
+                .filter(|other| *other != file_name)
+                .map(String::as_str)
+                .collect();
+            issues.push(Issue::new(
+                file_name,
+                IssueKind::DuplicateIsbn,
+                format!("ISBN {isbn} is also used by {}", others.join(", ")),
+            ));
+        }
+    }
+
+    let mut other_files: Vec<String> = WalkDir::new(data_path)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| !book_files.contains(name) && !KNOWN_FILES.contains(&name.as_str()))
+        .filter(|name| !covers.contains(&data_path.join(name)))
+        .collect();
+    other_files.sort();
+    for name in other_files {
+        issues.push(Issue::new(
+            &name,
+            IssueKind::UnexpectedFile,
+            "file name does not match NNNNN.toml".to_string(),
+        ));
+    }
+
+    // Stable, so the issues of each file stay in the order they were checked.
+    issues.sort_by(|a, b| a.file.cmp(&b.file));
+
+    issues
+}
+
+fn check_book(data_path: &Path, file_name: &str, data: &BookData) -> Vec<Issue> {
+    let mut issues = vec![];
+    let mut issue =
+        |kind, message: &str| issues.push(Issue::new(file_name, kind, message.to_string()));
+
+    if data.title.trim().is_empty() {
+        issue(IssueKind::EmptyTitle, "title is empty");
+    }
+    if data.price == 0 {
+        issue(IssueKind::ZeroPrice, "price is zero");
+    }
+
+    // Undated books are common, only impossible years are a problem.
+    let this_year = u16::try_from(Local::now().year()).unwrap_or(u16::MAX);
+    if data
+        .year
+        .earliest()
+        .is_some_and(|year| year < EARLIEST_YEAR)
+        || data.year.latest().is_some_and(|year| year > this_year)
+    {
+        issue(
+            IssueKind::InvalidYear,
+            &format!("year {} is out of range", data.year),
+        );
+    }
+
+    if let BookFormat::Other(format) = &data.format {
+        issue(
+            IssueKind::UnlistedFormat,
+            &format!("format {format:?} is neither a folding nor a height in cm"),
+        );
+    }
+
+    if data.keywords != clean_keywords(&data.keywords) {
+        issue(
+            IssueKind::KeywordWhitespace,
+            "keywords have stray whitespace or are empty",
+        );
+    }
+
+    if !data.cover.trim().is_empty() && !data_path.join(data.cover.trim()).exists() {
+        issue(
+            IssueKind::MissingCover,
+            &format!("cover file {} does not exist", data.cover),
+        );
+    }
+
+    issues
+}
+
+/// Keywords trimmed, with inner runs of whitespace collapsed and empty ones dropped.
+fn clean_keywords(keywords: &[String]) -> Vec<String> {
+    keywords
+        .iter()
+        .map(|keyword| keyword.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|keyword| !keyword.is_empty())
+        .collect()
+}
+
+/// Resolves the fixable issues of `data`. Returns whether anything changed.
+pub fn fix(data: &mut BookData) -> bool {
+    let keywords = clean_keywords(&data.keywords);
+    if keywords == data.keywords {
+        return false;
+    }
+    data.keywords = keywords;
+    true
+}
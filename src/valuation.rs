@@ -0,0 +1,420 @@
+//! Year-end valuation of the stock at purchase cost for the balance sheet.
+
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use crate::book::Book;
+use crate::book_data::{BookData, format_price};
+use crate::csv::csv_field;
+use crate::fl;
+use crate::invoice::Seller;
+use crate::labels::shorten;
+use crate::pdf::{Document, Font, Page, mm};
+use crate::sale::Sale;
+use crate::statistics::in_stock_since;
+use crate::status::Status;
+
+/// Lowers the market value of books held for at least `after_years` to the list
+/// price less `percent`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteDown {
+    pub after_years: u32,
+    pub percent: u32,
+}
+
+/// Write-downs for slow-moving stock applied unless configured otherwise.
+pub fn default_write_downs() -> Vec<WriteDown> {
+    vec![
+        WriteDown {
+            after_years: 3,
+            percent: 25,
+        },
+        WriteDown {
+            after_years: 5,
+            percent: 50,
+        },
+        WriteDown {
+            after_years: 10,
+            percent: 75,
+        },
+    ]
+}
+
+/// One book in the valuation. Amounts are in cents.
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub id: u32,
+    pub author: String,
+    pub title: String,
+    pub quantity: u32,
+    pub purchase_date: Option<NaiveDate>,
+    /// Cost of one copy, `None` if it was never recorded.
+    pub cost: Option<u32>,
+    /// Written down market value of one copy, if a write-down applies.
+    pub market: Option<u32>,
+    /// Value of all copies: the lower of cost and market value.
+    pub value: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Valuation {
+    pub date: NaiveDate,
+    pub write_downs: bool,
+    pub lines: Vec<Line>,
+}
+
+impl Valuation {
+    /// Values the copies on hand at `date`. The stock then is rebuilt from
+    /// today's: copies sold after `date` are added back, those returned after it
+    /// taken away again, and each book counts with the status it had at `date`,
+    /// so books sold or withdrawn by then are left out. Books bought after
+    /// `date` and print-on-demand titles, which are not kept in stock, are left
+    /// out, too. With `write_downs`, old stock is valued at the lower of cost
+    /// and market value.
+    pub fn compute(
+        books: &[Book],
+        sales: &[Sale],
+        date: NaiveDate,
+        write_downs: Option<&[WriteDown]>,
+    ) -> Self {
+        let mut lines = vec![];
+        for book in books {
+            let Some(data) = &book.data else {
+                continue;
+            };
+            let since = data.purchase_date.or_else(|| in_stock_since(book));
+            let quantity = (i64::from(data.quantity) + sold_after(sales, book.id, date))
+                .clamp(0, i64::from(u32::MAX)) as u32;
+            if data.unlimited
+                || quantity == 0
+                || matches!(status_at(data, date), Status::Sold | Status::Withdrawn)
+                || since.is_some_and(|since| since > date)
+            {
+                continue;
+            }
+
+            let percent = write_downs
+                .unwrap_or_default()
+                .iter()
+                .filter(|write_down| {
+                    since
+                        .and_then(|since| {
+                            since.checked_add_months(Months::new(12 * write_down.after_years))
+                        })
+                        .is_some_and(|from| from <= date)
+                })
+                .map(|write_down| write_down.percent.min(100))
+                .max();
+            let market = percent
+                .map(|percent| (u64::from(data.price) * u64::from(100 - percent) / 100) as u32);
+            let value = match (data.purchase_price, market) {
+                (Some(cost), Some(market)) => cost.min(market),
+                (Some(cost), None) => cost,
+                (None, _) => 0,
+            };
+
+            lines.push(Line {
+                id: book.id,
+                author: data.author.clone(),
+                title: data.title.clone(),
+                quantity,
+                purchase_date: data.purchase_date,
+                cost: data.purchase_price,
+                market,
+                value: value * quantity,
+            });
+        }
+        lines.sort_by_key(|line| line.id);
+
+        Valuation {
+            date,
+            write_downs: write_downs.is_some(),
+            lines,
+        }
+    }
+
+    /// Value of all lines at cost, before write-downs.
+    pub fn total_cost(&self) -> u32 {
+        self.lines
+            .iter()
+            .map(|line| line.cost.unwrap_or(0) * line.quantity)
+            .sum()
+    }
+
+    pub fn total_value(&self) -> u32 {
+        self.lines.iter().map(|line| line.value).sum()
+    }
+
+    /// Number of lines without a recorded purchase price, valued at zero.
+    pub fn without_cost(&self) -> usize {
+        self.lines.iter().filter(|line| line.cost.is_none()).count()
+    }
+
+    /// One row per book with amounts in euros with a decimal point.
+    pub fn to_csv(&self) -> String {
+        let euros = |cents: u32| format!("{}.{:02}", cents / 100, cents % 100);
+        let mut csv =
+            String::from("id,author,title,quantity,purchase_date,unit_cost,unit_market,value\n");
+        for line in &self.lines {
+            let fields = [
+                format!("{:05}", line.id),
+                line.author.clone(),
+                line.title.clone(),
+                line.quantity.to_string(),
+                line.purchase_date
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                line.cost.map(euros).unwrap_or_default(),
+                line.market.map(euros).unwrap_or_default(),
+                euros(line.value),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// A list of all lines with the totals, headed by the seller's letterhead.
+    pub fn to_pdf(&self, seller: &Seller) -> Document {
+        let mut document = Document::new();
+        let mut page = Page::a4();
+        let mut y = 277.0;
+        for (index, line) in seller.letterhead.iter().enumerate() {
+            let font = if index == 0 {
+                Font::Bold
+            } else {
+                Font::Regular
+            };
+            text(&mut page, LEFT, y, font, line);
+            y -= LINE;
+        }
+        y -= LINE;
+        page.text(
+            mm(LEFT),
+            mm(y),
+            16.0,
+            Font::Bold,
+            &fl!(
+                "inventory-valuation-at",
+                date = self.date.format("%d.%m.%Y").to_string()
+            ),
+        );
+        y -= LINE;
+        if self.write_downs {
+            text(
+                &mut page,
+                LEFT,
+                y,
+                Font::Regular,
+                &fl!("valuation-write-down-note"),
+            );
+        } else {
+            text(
+                &mut page,
+                LEFT,
+                y,
+                Font::Regular,
+                &fl!("valuation-cost-note"),
+            );
+        }
+        y -= 2.0 * LINE;
+        y = table_heading(&mut page, y);
+
+        for line in &self.lines {
+            if y < BOTTOM {
+                document.add_page(page);
+                page = Page::a4();
+                y = table_heading(&mut page, 277.0);
+            }
+
+            let description = format!("{}: {}", line.author, line.title);
+            let description = shorten(
+                &description,
+                mm(QUANTITY_COLUMN - 15.0 - DESCRIPTION_COLUMN),
+                SIZE,
+            );
+            text(
+                &mut page,
+                LEFT,
+                y,
+                Font::Regular,
+                &format!("{:05}", line.id),
+            );
+            text(
+                &mut page,
+                DESCRIPTION_COLUMN,
+                y,
+                Font::Regular,
+                &description,
+            );
+            text_right(
+                &mut page,
+                QUANTITY_COLUMN,
+                y,
+                Font::Regular,
+                &line.quantity.to_string(),
+            );
+            let cost = line
+                .cost
+                .map(format_price)
+                .unwrap_or_else(|| "–".to_string());
+            text_right(&mut page, COST_COLUMN, y, Font::Regular, &cost);
+            if let Some(market) = line.market {
+                text_right(
+                    &mut page,
+                    MARKET_COLUMN,
+                    y,
+                    Font::Regular,
+                    &format_price(market),
+                );
+            }
+            text_right(
+                &mut page,
+                RIGHT,
+                y,
+                Font::Regular,
+                &format_price(line.value),
+            );
+            y -= LINE;
+        }
+
+        if y < BOTTOM + 3.0 * LINE {
+            document.add_page(page);
+            page = Page::a4();
+            y = 277.0;
+        }
+        y += LINE - 2.0;
+        page.hline(mm(LEFT), mm(RIGHT), mm(y));
+        y -= LINE;
+        text(
+            &mut page,
+            DESCRIPTION_COLUMN,
+            y,
+            Font::Regular,
+            &fl!("total-at-cost"),
+        );
+        text_right(
+            &mut page,
+            RIGHT,
+            y,
+            Font::Regular,
+            &format_price(self.total_cost()),
+        );
+        y -= LINE;
+        text(
+            &mut page,
+            DESCRIPTION_COLUMN,
+            y,
+            Font::Bold,
+            &fl!("inventory-value"),
+        );
+        text_right(
+            &mut page,
+            RIGHT,
+            y,
+            Font::Bold,
+            &format_price(self.total_value()),
+        );
+        y -= 2.0 * LINE;
+        if self.without_cost() > 0 {
+            text(
+                &mut page,
+                LEFT,
+                y,
+                Font::Regular,
+                &fl!("books-without-cost", count = self.without_cost()),
+            );
+        }
+
+        document.add_page(page);
+        document
+    }
+}
+
+/// Copies of the book `book_id` sold after `date` less those returned after
+/// it, which is how many more copies were on hand then than now.
+fn sold_after(sales: &[Sale], book_id: u32, date: NaiveDate) -> i64 {
+    sales
+        .iter()
+        .flat_map(|sale| sale.items.iter().map(move |item| (sale.date, item)))
+        .filter(|(_, item)| item.book_id == book_id)
+        .map(|(sold, item)| {
+            i64::from(sold > date)
+                - i64::from(item.returned.is_some_and(|returned| returned > date))
+        })
+        .sum()
+}
+
+/// Status of the book at the end of `date`. Records without a history keep
+/// their current status, and books start out available.
+fn status_at(data: &BookData, date: NaiveDate) -> Status {
+    if data.status_history.is_empty() {
+        return data.status;
+    }
+    data.status_history
+        .iter()
+        .rev()
+        .find(|change| change.at.date_naive() <= date)
+        .map_or(Status::Available, |change| change.status)
+}
+
+pub fn reports_path(data_path: &Path) -> PathBuf {
+    data_path.join("reports")
+}
+
+/// Path of the valuation report at `date` with the file extension `extension`.
+pub fn valuation_path(data_path: &Path, date: NaiveDate, extension: &str) -> PathBuf {
+    reports_path(data_path).join(format!("valuation-{date}.{extension}"))
+}
+
+/// Writes `valuation` as CSV and PDF into the reports directory and returns the
+/// path of the PDF.
+pub fn write_valuation(
+    data_path: &Path,
+    seller: &Seller,
+    valuation: &Valuation,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    create_dir_all(reports_path(data_path))?;
+    write(
+        valuation_path(data_path, valuation.date, "csv"),
+        valuation.to_csv(),
+    )?;
+    let pdf = valuation_path(data_path, valuation.date, "pdf");
+    write(&pdf, valuation.to_pdf(seller).to_bytes())?;
+    Ok(pdf)
+}
+
+// Layout in millimetres from the bottom left corner of the page.
+const LEFT: f32 = 20.0;
+const RIGHT: f32 = 190.0;
+const BOTTOM: f32 = 20.0;
+const DESCRIPTION_COLUMN: f32 = LEFT + 14.0;
+const QUANTITY_COLUMN: f32 = 125.0;
+const COST_COLUMN: f32 = 145.0;
+const MARKET_COLUMN: f32 = 167.0;
+const SIZE: f32 = 9.0;
+const LINE: f32 = 4.5;
+
+fn text(page: &mut Page, x: f32, y: f32, font: Font, text: &str) {
+    page.text(mm(x), mm(y), SIZE, font, text);
+}
+
+fn text_right(page: &mut Page, x: f32, y: f32, font: Font, text: &str) {
+    page.text_right(mm(x), mm(y), SIZE, font, text);
+}
+
+/// Draws the column titles and returns the height of the first row.
+fn table_heading(page: &mut Page, mut y: f32) -> f32 {
+    text(page, LEFT, y, Font::Bold, &fl!("book-id"));
+    text(page, DESCRIPTION_COLUMN, y, Font::Bold, &fl!("description"));
+    text_right(page, QUANTITY_COLUMN, y, Font::Bold, &fl!("quantity"));
+    text_right(page, COST_COLUMN, y, Font::Bold, &fl!("unit-cost"));
+    text_right(page, MARKET_COLUMN, y, Font::Bold, &fl!("unit-market"));
+    text_right(page, RIGHT, y, Font::Bold, &fl!("value"));
+    y -= 2.0;
+    page.hline(mm(LEFT), mm(RIGHT), mm(y));
+    y - LINE
+}